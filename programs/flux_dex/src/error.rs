@@ -18,6 +18,20 @@ pub enum FluxDexError {
     InvalidAccount,
     #[msg("Account already initialized")]
     AccountAlreadyInitialized,
+    #[msg("Mint extension not supported")]
+    UnsupportedMintExtension,
+    #[msg("Invalid token metadata")]
+    InvalidMetadata,
+    #[msg("Pool is locked by an in-progress flash loan")]
+    PoolLocked,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Emergency mode is active")]
+    EmergencyModeActive,
+    #[msg("Emergency mode is not active")]
+    EmergencyModeNotActive,
+    #[msg("Feature disabled globally")]
+    FeatureDisabledGlobally,
 
     // 流动性管理错误 (100-199)
     #[msg("Invalid price range")]
@@ -36,6 +50,10 @@ pub enum FluxDexError {
     RebalanceThresholdNotMet,
     #[msg("Invalid risk profile")]
     InvalidRiskProfile,
+    #[msg("Reserves exceed vault balance")]
+    ReserveExceedsVaultBalance,
+    #[msg("Operation not supported by pool curve")]
+    InvalidCurveType,
 
     // 交易错误 (200-299)
     #[msg("Slippage tolerance exceeded")]
@@ -50,6 +68,8 @@ pub enum FluxDexError {
     PriceImpactTooHigh,
     #[msg("Minimum output not met")]
     MinimumOutputNotMet,
+    #[msg("Flash loan not repaid")]
+    FlashLoanNotRepaid,
 
     // MEV保护错误 (300-399)
     #[msg("Intent expired")]
@@ -80,6 +100,8 @@ pub enum FluxDexError {
     InvalidProposal,
     #[msg("Quorum not reached")]
     QuorumNotReached,
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
 
     // 数学计算错误 (500-599)
     #[msg("Division by zero")]
@@ -94,6 +116,12 @@ pub enum FluxDexError {
     PriceOutOfBounds,
     #[msg("Liquidity calculation failed")]
     LiquidityCalculationFailed,
+    #[msg("Invalid amplification coefficient")]
+    InvalidAmplification,
+    #[msg("Invalid token weight")]
+    InvalidWeight,
+    #[msg("Token decimals mismatch")]
+    MintDecimalsMismatch,
 
     // 跨链错误 (600-699)
     #[msg("Cross-chain transfer failed")]
@@ -0,0 +1,41 @@
+use crate::instructions::propose_authority::TransferPoolAuthority;
+use crate::state::AuthorityRole;
+use crate::AuthorityTransferAccepted;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+/// 待接受者签名接受角色，移交在此时生效
+pub fn accept_authority_handler(
+    ctx: Context<TransferPoolAuthority>,
+    role: AuthorityRole, // 要接受的角色
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let pool = &mut ctx.accounts.pool;
+    let pending = pool.pending_role_holder(role);
+
+    if pending == Pubkey::default() {
+        return Err(FluxDexError::NoPendingAuthority.into());
+    }
+
+    if ctx.accounts.signer.key() != pending {
+        return Err(FluxDexError::Unauthorized.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let old = pool.role_holder(role);
+    pool.accept_role(role);
+    pool.updated_at = now;
+
+    emit!(AuthorityTransferAccepted {
+        pool: pool.key(),
+        role,
+        old,
+        new: pending,
+        timestamp: now,
+    });
+
+    msg!("✅ {:?} transferred: {} -> {}", role, old, pending);
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::{add_liquidity_delta, Pool, PriceObservations, TickArray};
+use crate::utils::ConcentratedMath;
+use crate::FluxDexError;
+use crate::Position;
+use crate::RangeLiquidityAdded;
+use crate::{amount_with_transfer_fee, transfer_tokens_to_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct AddConcentratedLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// 包含区间下界 tick 的 TickArray
+    #[account(
+        mut,
+        constraint = tick_array_lower.pool == pool.key() @ FluxDexError::InvalidAccount,
+    )]
+    pub tick_array_lower: Account<'info, TickArray>,
+
+    /// 包含区间上界 tick 的 TickArray（可与下界相同）
+    #[account(
+        mut,
+        constraint = tick_array_upper.pool == pool.key() @ FluxDexError::InvalidAccount,
+    )]
+    pub tick_array_upper: Account<'info, TickArray>,
+
+    /// 用户在该区间的 Position 账户 (如果不存在会创建)，每个区间一个头寸
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = Position::SIZE,
+        seeds = [
+            POSITION_SEED,
+            user.key().as_ref(),
+            pool.key().as_ref(),
+            &tick_lower.to_le_bytes(),
+            &tick_upper.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn add_concentrated_liquidity_handler(
+    ctx: Context<AddConcentratedLiquidity>,
+    tick_lower: i32,   // 区间下界 tick
+    tick_upper: i32,   // 区间上界 tick
+    liquidity: u128,   // 要添加的流动性
+    max_amount_a: u64, // 最多存入的 Token A 数量（滑点保护）
+    max_amount_b: u64, // 最多存入的 Token B 数量（滑点保护）
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    pool.validate_tick_range(tick_lower, tick_upper)?;
+
+    if liquidity == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| FluxDexError::Overflow)?;
+
+    // Position 由区间派生，流动性为零时（重新）开立区间头寸
+    let position = &ctx.accounts.position;
+    let is_new_range = position.liquidity == 0 && position.lp_tokens == 0;
+
+    // 存入量向上取整
+    let (amount_a, amount_b) = ConcentratedMath::amounts_for_liquidity(
+        pool.current_tick,
+        pool.sqrt_price_x64,
+        tick_lower,
+        tick_upper,
+        liquidity,
+        true,
+    )?;
+    let amount_a = u64::try_from(amount_a).map_err(|_| FluxDexError::Overflow)?;
+    let amount_b = u64::try_from(amount_b).map_err(|_| FluxDexError::Overflow)?;
+
+    // 带转账手续费的 mint 需要多转入手续费部分，保证金库足额到账
+    let gross_amount_a = amount_with_transfer_fee(&ctx.accounts.token_a_mint, amount_a)?;
+    let gross_amount_b = amount_with_transfer_fee(&ctx.accounts.token_b_mint, amount_b)?;
+
+    // 检查滑点保护
+    if gross_amount_a > max_amount_a || gross_amount_b > max_amount_b {
+        return Err(FluxDexError::SlippageExceeded.into());
+    }
+
+    // check user Balance
+    if ctx.accounts.user_token_a.amount < gross_amount_a
+        || ctx.accounts.user_token_b.amount < gross_amount_b
+    {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    // 执行代币转账：用户 -> 池子，按金库实际到账数量记账
+    let received_a = transfer_tokens_to_pool(
+        &ctx.accounts.user,
+        &ctx.accounts.user_token_a,
+        &mut ctx.accounts.pool_token_a_vault,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program,
+        gross_amount_a,
+    )?;
+
+    let received_b = transfer_tokens_to_pool(
+        &ctx.accounts.user,
+        &ctx.accounts.user_token_b,
+        &mut ctx.accounts.pool_token_b_vault,
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program,
+        gross_amount_b,
+    )?;
+
+    if received_a < amount_a || received_b < amount_b {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    // 储备量变化前记录价格观测
+    let clock = Clock::get()?;
+    ctx.accounts
+        .price_oracle
+        .observe(&mut ctx.accounts.pool, clock.unix_timestamp)?;
+
+    if is_new_range {
+        ctx.accounts.position.open_range(
+            ctx.accounts.user.key(),
+            ctx.accounts.pool.key(),
+            tick_lower,
+            tick_upper,
+            ctx.bumps.position,
+            clock.unix_timestamp,
+        )?;
+    }
+
+    modify_range_liquidity(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.position,
+        &mut ctx.accounts.tick_array_lower,
+        &mut ctx.accounts.tick_array_upper,
+        liquidity_delta,
+    )?;
+    ctx.accounts
+        .position
+        .record_range_deposit(received_a, received_b, liquidity)?;
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_add(received_a)
+        .ok_or(FluxDexError::Overflow)?;
+
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_add(received_b)
+        .ok_or(FluxDexError::Overflow)?;
+
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(RangeLiquidityAdded {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        tick_lower,
+        tick_upper,
+        liquidity,
+        amount_a: received_a,
+        amount_b: received_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Range liquidity added: {} token A, {} token B, liquidity {}",
+        received_a,
+        received_b,
+        liquidity
+    );
+    Ok(())
+}
+
+/// 更新区间边界 tick 与池子活跃流动性，并结算头寸在区间内累计的手续费
+///
+/// 上下界位于同一个 TickArray 时两个账户相同，统一在 tick_array_lower 上修改后同步
+pub(crate) fn modify_range_liquidity(
+    pool: &mut Account<Pool>,
+    position: &mut Position,
+    tick_array_lower: &mut Account<TickArray>,
+    tick_array_upper: &mut Account<TickArray>,
+    liquidity_delta: i128,
+) -> Result<()> {
+    let (tick_lower, tick_upper) = (position.tick_lower_index, position.tick_upper_index);
+    let spacing = pool.tick_spacing;
+    let same_array = tick_array_lower.key() == tick_array_upper.key();
+
+    pool.update_tick(tick_array_lower, tick_lower, liquidity_delta, false)?;
+    let upper_array: &mut TickArray = if same_array {
+        tick_array_lower
+    } else {
+        tick_array_upper
+    };
+    pool.update_tick(upper_array, tick_upper, liquidity_delta, true)?;
+    let upper = *upper_array.tick(tick_upper, spacing)?;
+    let lower = *tick_array_lower.tick(tick_lower, spacing)?;
+
+    // 先按更新后的 tick 结算手续费，再清空不再使用的 tick
+    let (fee_growth_inside_a, fee_growth_inside_b) =
+        pool.fee_growth_inside(tick_lower, &lower, tick_upper, &upper);
+    position.accrue_range_fees(fee_growth_inside_a, fee_growth_inside_b)?;
+
+    tick_array_lower.clear_if_unused(tick_lower, spacing)?;
+    if same_array {
+        tick_array_lower.clear_if_unused(tick_upper, spacing)?;
+        tick_array_upper.set_inner((**tick_array_lower).clone());
+    } else {
+        tick_array_upper.clear_if_unused(tick_upper, spacing)?;
+    }
+
+    // 当前价格在区间内时同步活跃流动性
+    if tick_lower <= pool.current_tick && pool.current_tick < tick_upper {
+        pool.liquidity = add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+    }
+    Ok(())
+}
@@ -1,18 +1,16 @@
 use crate::state::seeds::*;
+use crate::state::GlobalConfig;
 use crate::state::Pool;
+use crate::state::PriceObservations;
 use crate::FluxDexError;
 use crate::LiquidityAdded;
-use crate::MathUtils;
 use crate::Position;
-use crate::RiskProfile;
-use crate::StrategyType;
+use crate::MIN_LIQUIDITY_THRESHOLD;
+use crate::{mint_lp_tokens, transfer_tokens_to_pool};
 use anchor_lang::prelude::*;
-use anchor_spl::token;
-use anchor_spl::token::Mint;
-use anchor_spl::token::MintTo;
-use anchor_spl::token::Token;
-use anchor_spl::token::TokenAccount;
-use anchor_spl::token::Transfer;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
 
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
@@ -21,24 +19,30 @@ pub struct AddLiquidity<'info> {
 
     #[account(
         mut,
-        seeds = [POOL_SEED,pool.authority.as_ref()],
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
         bump = pool.bump
     )]
     pub pool: Account<'info, Pool>,
 
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
     #[account(
       mut,
       constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
       constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
     )]
-    pub user_token_a: Account<'info, TokenAccount>,
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
         constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
     )]
-    pub user_token_b: Account<'info, TokenAccount>,
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
@@ -46,40 +50,74 @@ pub struct AddLiquidity<'info> {
         token::mint = lp_mint,
         token::authority = user,
     )]
-    pub user_lp_token: Account<'info, TokenAccount>,
+    pub user_lp_token: InterfaceAccount<'info, TokenAccount>,
 
     // Pool 的  token A 金库
     #[account(
         mut,
         constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
     )]
-    pub pool_token_a_vault: Account<'info, TokenAccount>,
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
     )]
-    pub pool_token_b_vault: Account<'info, TokenAccount>,
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// LP Token Mint (Pool 拥有铸造权限)
     #[account(
         mut,
         constraint = lp_mint.key() == pool.lp_mint @ FluxDexError::InvalidAccount,
     )]
-    pub lp_mint: Account<'info, Mint>,
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// 永久锁定的 LP 账户
+    #[account(
+        mut,
+        seeds = [LOCKED_LP_SEED, pool.key().as_ref()],
+        bump,
+    )]
+    pub locked_lp_token: InterfaceAccount<'info, TokenAccount>,
 
     /// 用户的 Position 账户 (如果不存在会创建)
     #[account(
         init_if_needed,
         payer = user,
         space = Position::SIZE,
-        seeds = [b"position", user.key().as_ref(), pool.key().as_ref()],
+        seeds = [POSITION_SEED, user.key().as_ref(), pool.key().as_ref()],
         bump,
     )]
     pub position: Account<'info, Position>,
 
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// LP 代币所属的代币程序
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
@@ -94,10 +132,10 @@ pub fn add_liquidity_handler(
     let pool = &mut ctx.accounts.pool;
     let position = &mut ctx.accounts.position;
 
-    // check：：pool is stop
-    if pool.paused || pool.emergency_mode {
-        return Err(FluxDexError::Unauthorized.into());
-    }
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
 
     // check:: input pargam
     if desired_amount_a == 0 || desired_amount_b == 0 {
@@ -136,55 +174,79 @@ pub fn add_liquidity_handler(
         return Err(FluxDexError::SlippageExceeded.into());
     }
 
-    // 计算应该铸造的 LP 代币数量
-    let lp_tokens_to_mint = MathUtils::calculate_lp_tokens(
-        actual_amount_a,
-        actual_amount_b,
-        pool.token_a_reserve,
-        pool.token_b_reserve,
-        pool.lp_supply,
-    )?;
-
-    // 检查最小 LP 代币数量
-    if lp_tokens_to_mint < min_lp_tokens {
-        return Err(FluxDexError::SlippageExceeded.into());
-    }
-
-    // 执行代币转账：用户 -> 池子
-    transfer_tokens_to_pool(
+    // 执行代币转账：用户 -> 池子，按金库实际到账数量记账（兼容转账手续费）
+    let received_a = transfer_tokens_to_pool(
         &ctx.accounts.user,
         &ctx.accounts.user_token_a,
-        &ctx.accounts.pool_token_a_vault,
-        &ctx.accounts.token_program,
+        &mut ctx.accounts.pool_token_a_vault,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program,
         actual_amount_a,
     )?;
 
-    transfer_tokens_to_pool(
+    let received_b = transfer_tokens_to_pool(
         &ctx.accounts.user,
         &ctx.accounts.user_token_b,
-        &ctx.accounts.pool_token_b_vault,
-        &ctx.accounts.token_program,
+        &mut ctx.accounts.pool_token_b_vault,
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program,
         actual_amount_b,
     )?;
 
+    // 计算应该铸造的 LP 代币数量
+    let clock = Clock::get()?;
+    let lp_tokens_to_mint =
+        pool.calculate_lp_tokens(received_a, received_b, clock.unix_timestamp)?;
+
+    // 首次存入时永久锁定最小流动性，防止份额通胀攻击
+    let locked_lp_tokens = if pool.lp_supply == 0 {
+        if lp_tokens_to_mint <= MIN_LIQUIDITY_THRESHOLD {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+        MIN_LIQUIDITY_THRESHOLD
+    } else {
+        0
+    };
+    let user_lp_tokens = lp_tokens_to_mint - locked_lp_tokens;
+
+    // 检查最小 LP 代币数量
+    if user_lp_tokens < min_lp_tokens {
+        return Err(FluxDexError::SlippageExceeded.into());
+    }
+
     // 铸造 LP 代币给用户
     mint_lp_tokens(
-        &pool,
+        pool,
         &ctx.accounts.lp_mint,
         &ctx.accounts.user_lp_token,
         &ctx.accounts.token_program,
-        lp_tokens_to_mint,
+        user_lp_tokens,
     )?;
 
+    if locked_lp_tokens > 0 {
+        mint_lp_tokens(
+            pool,
+            &ctx.accounts.lp_mint,
+            &ctx.accounts.locked_lp_token,
+            &ctx.accounts.token_program,
+            locked_lp_tokens,
+        )?;
+    }
+
+    // 储备量变化前记录价格观测
+    ctx.accounts
+        .price_oracle
+        .observe(pool, clock.unix_timestamp)?;
+
     // 更新池子状态
     pool.token_a_reserve = pool
         .token_a_reserve
-        .checked_add(actual_amount_a)
+        .checked_add(received_a)
         .ok_or(FluxDexError::Overflow)?;
 
     pool.token_b_reserve = pool
         .token_b_reserve
-        .checked_add(actual_amount_b)
+        .checked_add(received_b)
         .ok_or(FluxDexError::Overflow)?;
 
     pool.lp_supply = pool
@@ -192,65 +254,43 @@ pub fn add_liquidity_handler(
         .checked_add(lp_tokens_to_mint)
         .ok_or(FluxDexError::Overflow)?;
 
-    let clock = Clock::get()?;
     pool.updated_at = clock.unix_timestamp;
 
-    // 初始化或更新用户 Position
-    if position.lp_tokens == 0 {
-        // 首次添加流动性，初始化 Position
-        position.owner = ctx.accounts.user.key();
-        position.pool = ctx.accounts.pool.key();
-        position.strategy_type = StrategyType::Passive; // 默认被动策略
-        position.risk_profile = RiskProfile::Balanced; // 默认平衡风险
-        position.auto_rebalance = false;
-        position.rebalance_threshold_bps = 500; // 5% 阈值
-
-        position.initial_deposit_a = actual_amount_a;
-        position.initial_deposit_b = actual_amount_b;
-        position.realized_fees_a = 0;
-        position.realized_fees_b = 0;
-        position.unrealized_pnl_a = 0;
-        position.unrealized_pnl_b = 0;
-
-        position.mev_rewards_earned = 0;
-        position.mev_rewards_claimed = 0;
-
-        position.created_at = clock.unix_timestamp;
-        position.last_rebalanced = clock.unix_timestamp;
-        position.bump = ctx.bumps.position;
-    } else {
-        // 增加现有 Position
-        position.initial_deposit_a = position
-            .initial_deposit_a
-            .checked_add(actual_amount_a)
-            .ok_or(FluxDexError::Overflow)?;
-
-        position.initial_deposit_b = position
-            .initial_deposit_b
-            .checked_add(actual_amount_b)
-            .ok_or(FluxDexError::Overflow)?;
-    }
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
 
-    position.lp_tokens = position
-        .lp_tokens
-        .checked_add(lp_tokens_to_mint)
-        .ok_or(FluxDexError::Overflow)?;
+    // 初始化或更新用户 Position（先结算已有 LP 的手续费）
+    position.accrue_fees(&ctx.accounts.pool)?;
+    position.record_deposit(
+        ctx.accounts.user.key(),
+        ctx.accounts.pool.key(),
+        received_a,
+        received_b,
+        user_lp_tokens,
+        ctx.bumps.position,
+        clock.unix_timestamp,
+    )?;
 
     // 发出事件
     emit!(LiquidityAdded {
         user: ctx.accounts.user.key(),
         pool: ctx.accounts.pool.key(),
-        amount_a: actual_amount_a,
-        amount_b: actual_amount_b,
-        lp_tokens: lp_tokens_to_mint,
+        amount_a: received_a,
+        amount_b: received_b,
+        lp_tokens: user_lp_tokens,
         timestamp: clock.unix_timestamp,
     });
 
     msg!(
         "✅ Liquidity added: {} token A, {} token B, {} LP tokens minted",
-        actual_amount_a,
-        actual_amount_b,
-        lp_tokens_to_mint
+        received_a,
+        received_b,
+        user_lp_tokens
     );
     Ok(())
 }
@@ -276,46 +316,3 @@ fn calculate_optimal_amounts(
         Ok((optimal_a as u64, desired_b))
     }
 }
-
-// 辅助函数：转账代币到池子
-fn transfer_tokens_to_pool<'info>(
-    user: &Signer<'info>,
-    user_token_account: &Account<'info, TokenAccount>,
-    pool_vault: &Account<'info, TokenAccount>,
-    token_program: &Program<'info, Token>,
-    amount: u64,
-) -> Result<()> {
-    let cpi_accounts = Transfer {
-        from: user_token_account.to_account_info(),
-        to: pool_vault.to_account_info(),
-        authority: user.to_account_info(),
-    };
-    let cpi_program = token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-
-    token::transfer(cpi_ctx, amount)?;
-    Ok(())
-}
-
-// 辅助函数：铸造 LP 代币
-fn mint_lp_tokens<'info>(
-    pool: &Account<'info, Pool>,
-    lp_mint: &Account<'info, Mint>,
-    user_lp_account: &Account<'info, TokenAccount>,
-    token_program: &Program<'info, Token>,
-    amount: u64,
-) -> Result<()> {
-    let authority_seed = &[POOL_SEED, pool.authority.as_ref(), &[pool.bump]];
-    let signer = &[&authority_seed[..]];
-
-    let cpi_accounts = MintTo {
-        mint: lp_mint.to_account_info(),
-        to: user_lp_account.to_account_info(),
-        authority: pool.to_account_info(),
-    };
-    let cpi_program = token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-
-    token::mint_to(cpi_ctx, amount)?;
-    Ok(())
-}
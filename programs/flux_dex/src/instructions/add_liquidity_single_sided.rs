@@ -0,0 +1,292 @@
+use crate::instructions::swap::update_dynamic_fee;
+use crate::state::seeds::*;
+use crate::state::CurveType;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::FluxDexError;
+use crate::LiquidityAdded;
+use crate::MathUtils;
+use crate::Position;
+use crate::{mint_lp_tokens, transfer_tokens_to_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct AddLiquiditySingleSided<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// 用户存入的代币账户（Token A 或 Token B）
+    #[account(
+        mut,
+        constraint = (user_token_in.mint == pool.token_a_mint || user_token_in.mint == pool.token_b_mint) @ FluxDexError::InvalidAccount,
+        constraint = user_token_in.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_in: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        token::mint = lp_mint,
+        token::authority = user,
+    )]
+    pub user_lp_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = Position::SIZE,
+        seeds = [POSITION_SEED, user.key().as_ref(), pool.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// LP 代币所属的代币程序
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn add_liquidity_single_sided_handler(
+    ctx: Context<AddLiquiditySingleSided>,
+    amount_in: u64,     // 单边存入的代币数量
+    min_lp_tokens: u64, // 最少获得的 LP Token 数量（滑点保护）
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    if amount_in == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    // 单边存入需要池子已有流动性作为定价依据
+    if pool.lp_supply == 0 {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    if ctx.accounts.user_token_in.amount < amount_in {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    let a_to_b = ctx.accounts.user_token_in.mint == pool.token_a_mint;
+    let fee_bps = pool.current_fee_bps;
+    let protocol_fee_bps = ctx.accounts.global_config.protocol_fee_bps;
+
+    // 执行代币转账：用户 -> 池子（兑换所得从未离开金库），按金库实际到账数量计算
+    let (pool_vault, mint_in, token_program_in) = if a_to_b {
+        (
+            &mut ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+        )
+    } else {
+        (
+            &mut ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+        )
+    };
+    let amount_received = transfer_tokens_to_pool(
+        &ctx.accounts.user,
+        &ctx.accounts.user_token_in,
+        pool_vault,
+        mint_in,
+        token_program_in,
+        amount_in,
+    )?;
+
+    // 储备量变化前记录价格观测
+    let clock = Clock::get()?;
+    ctx.accounts
+        .price_oracle
+        .observe(pool, clock.unix_timestamp)?;
+    let pre_swap_price = pool.get_current_price(clock.unix_timestamp);
+
+    let (swap_amount, deposit_a, deposit_b, lp_tokens_to_mint) = if pool.curve_type
+        == CurveType::Weighted
+    {
+        // 加权池按单资产加入公式直接铸造，超出自身权重的部分收取手续费
+        let (lp_tokens, fee_amount) =
+            pool.calculate_single_sided_lp(amount_received, a_to_b, fee_bps)?;
+        pool.credit_fee(a_to_b, fee_amount, protocol_fee_bps)?;
+
+        let deposit_in = amount_received - fee_amount;
+        let (deposit_a, deposit_b) = if a_to_b {
+            (deposit_in, 0)
+        } else {
+            (0, deposit_in)
+        };
+        (0, deposit_a, deposit_b, lp_tokens)
+    } else {
+        // 先在池内兑换一部分，使剩余部分与兑换所得恰好符合池子比例
+        let swap_amount =
+            pool.calculate_zap_swap_amount(amount_received, a_to_b, fee_bps, clock.unix_timestamp)?;
+        let swap_output =
+            pool.calculate_swap_output(swap_amount, a_to_b, fee_bps, clock.unix_timestamp)?;
+        if swap_output == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+
+        let fee_amount = MathUtils::calculate_fee_amount(swap_amount, fee_bps)?;
+        pool.record_swap(
+            a_to_b,
+            swap_amount,
+            swap_output,
+            fee_amount,
+            protocol_fee_bps,
+        )?;
+
+        // 以兑换后的储备量计算 LP
+        let deposit_in = amount_received - swap_amount;
+        let (deposit_a, deposit_b) = if a_to_b {
+            (deposit_in, swap_output)
+        } else {
+            (swap_output, deposit_in)
+        };
+        let lp_tokens = pool.calculate_lp_tokens(deposit_a, deposit_b, clock.unix_timestamp)?;
+        (swap_amount, deposit_a, deposit_b, lp_tokens)
+    };
+
+    if lp_tokens_to_mint == 0 || lp_tokens_to_mint < min_lp_tokens {
+        return Err(FluxDexError::SlippageExceeded.into());
+    }
+
+    // 铸造 LP 代币给用户
+    mint_lp_tokens(
+        &ctx.accounts.pool,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp_token,
+        &ctx.accounts.token_program,
+        lp_tokens_to_mint,
+    )?;
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_add(deposit_a)
+        .ok_or(FluxDexError::Overflow)?;
+
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_add(deposit_b)
+        .ok_or(FluxDexError::Overflow)?;
+
+    pool.lp_supply = pool
+        .lp_supply
+        .checked_add(lp_tokens_to_mint)
+        .ok_or(FluxDexError::Overflow)?;
+
+    update_dynamic_fee(
+        pool,
+        &ctx.accounts.price_oracle,
+        pre_swap_price,
+        clock.unix_timestamp,
+    )?;
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    // Position 按用户实际投入记录
+    let (contributed_a, contributed_b) = if a_to_b {
+        (amount_received, 0)
+    } else {
+        (0, amount_received)
+    };
+    ctx.accounts.position.accrue_fees(&ctx.accounts.pool)?;
+    ctx.accounts.position.record_deposit(
+        ctx.accounts.user.key(),
+        ctx.accounts.pool.key(),
+        contributed_a,
+        contributed_b,
+        lp_tokens_to_mint,
+        ctx.bumps.position,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(LiquidityAdded {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        amount_a: contributed_a,
+        amount_b: contributed_b,
+        lp_tokens: lp_tokens_to_mint,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Single-sided liquidity added: {} in ({} swapped), {} LP tokens minted",
+        amount_received,
+        swap_amount,
+        lp_tokens_to_mint
+    );
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::instructions::propose_authority::TransferPoolAuthority;
+use crate::state::AuthorityRole;
+use crate::AuthorityTransferCancelled;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+/// 角色当前持有者撤回尚未接受的移交提议
+pub fn cancel_authority_transfer_handler(
+    ctx: Context<TransferPoolAuthority>,
+    role: AuthorityRole, // 要撤回移交的角色
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let pool = &mut ctx.accounts.pool;
+    let current = pool.role_holder(role);
+
+    if ctx.accounts.signer.key() != current {
+        return Err(FluxDexError::Unauthorized.into());
+    }
+
+    let cancelled = pool.pending_role_holder(role);
+    if cancelled == Pubkey::default() {
+        return Err(FluxDexError::NoPendingAuthority.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    pool.set_pending_role_holder(role, Pubkey::default());
+    pool.updated_at = now;
+
+    emit!(AuthorityTransferCancelled {
+        pool: pool.key(),
+        role,
+        current,
+        cancelled,
+        timestamp: now,
+    });
+
+    msg!("✅ {:?} transfer to {} cancelled", role, cancelled);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::FluxDexError;
+use crate::Position;
+use crate::PositionClosed;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// 关闭后租金退还给 owner
+    ///
+    /// LP 头寸与区间头寸的种子不同，只校验归属
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ FluxDexError::Unauthorized,
+        has_one = pool @ FluxDexError::InvalidAccount,
+    )]
+    pub position: Account<'info, Position>,
+}
+
+pub fn close_position_handler(ctx: Context<ClosePosition>) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let position = &ctx.accounts.position;
+
+    // 仍持有 LP 或有未领取收益时不允许关闭
+    if !position.is_empty() {
+        return Err(FluxDexError::PositionNotEmpty.into());
+    }
+
+    let clock = Clock::get()?;
+    emit!(PositionClosed {
+        owner: ctx.accounts.owner.key(),
+        pool: ctx.accounts.pool.key(),
+        position: position.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ Position closed: {}", position.key());
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::transfer_tokens_from_pool;
+use crate::FeesCollected;
+use crate::FluxDexError;
+use crate::Position;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, user.key().as_ref(), pool.key().as_ref()],
+        bump = position.bump,
+        constraint = position.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+/// 领取 Position 累计的 LP 手续费，不销毁 LP
+pub fn collect_fees_handler(ctx: Context<CollectFees>) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+    ctx.accounts.pool.check_unlocked()?;
+    let position = &mut ctx.accounts.position;
+    position.accrue_fees(&ctx.accounts.pool)?;
+
+    let amount_a = position.fees_owed_a;
+    let amount_b = position.fees_owed_b;
+
+    if amount_a == 0 && amount_b == 0 {
+        return Err(FluxDexError::InsufficientFeeBalance.into());
+    }
+
+    // 执行代币转账：池子 -> 用户
+    if amount_a > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.user_token_a,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+            amount_a,
+        )?;
+    }
+
+    if amount_b > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.user_token_b,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+            amount_b,
+        )?;
+    }
+
+    // 更新 Position
+    let position = &mut ctx.accounts.position;
+    position.fees_owed_a = 0;
+    position.fees_owed_b = 0;
+    position.realized_fees_a = position
+        .realized_fees_a
+        .checked_add(amount_a)
+        .ok_or(FluxDexError::Overflow)?;
+    position.realized_fees_b = position
+        .realized_fees_b
+        .checked_add(amount_b)
+        .ok_or(FluxDexError::Overflow)?;
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    pool.unclaimed_lp_fees_a = pool
+        .unclaimed_lp_fees_a
+        .checked_sub(amount_a)
+        .ok_or(FluxDexError::InsufficientFeeBalance)?;
+    pool.unclaimed_lp_fees_b = pool
+        .unclaimed_lp_fees_b
+        .checked_sub(amount_b)
+        .ok_or(FluxDexError::InsufficientFeeBalance)?;
+
+    let clock = Clock::get()?;
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(FeesCollected {
+        owner: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        amount_a,
+        amount_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Fees collected: {} token A, {} token B",
+        amount_a,
+        amount_b
+    );
+    Ok(())
+}
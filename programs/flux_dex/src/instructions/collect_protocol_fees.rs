@@ -0,0 +1,146 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::transfer_tokens_from_pool;
+use crate::FluxDexError;
+use crate::ProtocolFeesCollected;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        has_one = super_admin @ FluxDexError::Unauthorized,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // 国库的 Token A / Token B 账户
+    #[account(
+        mut,
+        constraint = treasury_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = treasury_token_a.owner == global_config.treasury @ FluxDexError::InvalidAccount,
+    )]
+    pub treasury_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = treasury_token_b.owner == global_config.treasury @ FluxDexError::InvalidAccount,
+    )]
+    pub treasury_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+pub fn collect_protocol_fees_handler(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+    ctx.accounts.pool.check_unlocked()?;
+    let amount_a = ctx.accounts.pool.protocol_fees_a;
+    let amount_b = ctx.accounts.pool.protocol_fees_b;
+
+    if amount_a == 0 && amount_b == 0 {
+        return Err(FluxDexError::InsufficientFeeBalance.into());
+    }
+
+    // 执行代币转账：池子 -> 国库
+    if amount_a > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.treasury_token_a,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+            amount_a,
+        )?;
+    }
+
+    if amount_b > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.treasury_token_b,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+            amount_b,
+        )?;
+    }
+
+    let clock = Clock::get()?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    let config = &mut ctx.accounts.global_config;
+    config.total_fees_collected = config
+        .total_fees_collected
+        .checked_add(amount_a)
+        .ok_or(FluxDexError::Overflow)?
+        .checked_add(amount_b)
+        .ok_or(FluxDexError::Overflow)?;
+
+    emit!(ProtocolFeesCollected {
+        pool: ctx.accounts.pool.key(),
+        treasury: ctx.accounts.global_config.treasury,
+        amount_a,
+        amount_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Protocol fees collected: {} token A, {} token B",
+        amount_a,
+        amount_b
+    );
+    Ok(())
+}
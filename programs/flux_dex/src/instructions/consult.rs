@@ -0,0 +1,47 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::state::TwapPrice;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Consult<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+}
+
+/// 只读查询 TWAP，供其他程序通过 CPI 返回值读取
+pub fn consult_handler(ctx: Context<Consult>, window_secs: u32) -> Result<TwapPrice> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let clock = Clock::get()?;
+    let twap =
+        ctx.accounts
+            .price_oracle
+            .consult(&ctx.accounts.pool, clock.unix_timestamp, window_secs)?;
+
+    msg!(
+        "TWAP over {}s: price_a={}, price_b={}",
+        twap.window_secs,
+        twap.price_a.value,
+        twap.price_b.value
+    );
+    Ok(twap)
+}
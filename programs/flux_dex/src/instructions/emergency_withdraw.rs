@@ -0,0 +1,244 @@
+use crate::state::seeds::*;
+use crate::state::CurveType;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::EmergencyWithdrawn;
+use crate::FluxDexError;
+use crate::MathUtils;
+use crate::Position;
+use crate::{burn_lp_tokens, transfer_tokens_from_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token.mint == pool.lp_mint @ FluxDexError::InvalidAccount,
+        constraint = user_lp_token.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_lp_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// 用户的 Position 账户
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, user.key().as_ref(), pool.key().as_ref()],
+        bump = position.bump,
+        constraint = position.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// LP 代币所属的代币程序
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// 紧急模式下 LP 按份额赎回储备，并一并领取累计的手续费
+///
+/// 不检查暂停开关，不更新价格观测与动态费率。紧急模式下兑换被拒绝，
+/// 储备量不会变化，因此不需要滑点参数
+pub fn emergency_withdraw_handler(
+    ctx: Context<EmergencyWithdraw>,
+    lp_amount: u64, // 要赎回的 LP 代币数量
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    if !pool.emergency_mode && !ctx.accounts.global_config.emergency_mode {
+        return Err(FluxDexError::EmergencyModeNotActive.into());
+    }
+    // 闪电贷回调中金库余额尚未归还
+    pool.check_unlocked()?;
+
+    // 集中流动性仓位不持有 LP 代币，通过 emergency_withdraw_range 赎回
+    if pool.curve_type == CurveType::Concentrated {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    if lp_amount == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    if ctx.accounts.user_lp_token.amount < lp_amount || ctx.accounts.position.lp_tokens < lp_amount
+    {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    // 按储备比例计算，不经过曲线
+    let (amount_a, amount_b) = MathUtils::calculate_remove_amounts(
+        lp_amount,
+        pool.lp_supply,
+        pool.token_a_reserve,
+        pool.token_b_reserve,
+    )?;
+
+    // 先按赎回前的 LP 数量结算手续费
+    let position = &mut ctx.accounts.position;
+    position.accrue_fees(&ctx.accounts.pool)?;
+    let fees_a = position.fees_owed_a;
+    let fees_b = position.fees_owed_b;
+    position.fees_owed_a = 0;
+    position.fees_owed_b = 0;
+    position.realized_fees_a = position
+        .realized_fees_a
+        .checked_add(fees_a)
+        .ok_or(FluxDexError::Overflow)?;
+    position.realized_fees_b = position
+        .realized_fees_b
+        .checked_add(fees_b)
+        .ok_or(FluxDexError::Overflow)?;
+    position.record_withdrawal(lp_amount)?;
+
+    let payout_a = amount_a.checked_add(fees_a).ok_or(FluxDexError::Overflow)?;
+    let payout_b = amount_b.checked_add(fees_b).ok_or(FluxDexError::Overflow)?;
+
+    // 销毁用户 LP 代币
+    burn_lp_tokens(
+        &ctx.accounts.pool,
+        &ctx.accounts.user,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp_token,
+        &ctx.accounts.token_program,
+        lp_amount,
+    )?;
+
+    // 执行代币转账：池子 -> 用户
+    if payout_a > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.user_token_a,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+            payout_a,
+        )?;
+    }
+
+    if payout_b > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.user_token_b,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+            payout_b,
+        )?;
+    }
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_sub(amount_a)
+        .ok_or(FluxDexError::Underflow)?;
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_sub(amount_b)
+        .ok_or(FluxDexError::Underflow)?;
+    pool.lp_supply = pool
+        .lp_supply
+        .checked_sub(lp_amount)
+        .ok_or(FluxDexError::Underflow)?;
+    pool.unclaimed_lp_fees_a = pool
+        .unclaimed_lp_fees_a
+        .checked_sub(fees_a)
+        .ok_or(FluxDexError::InsufficientFeeBalance)?;
+    pool.unclaimed_lp_fees_b = pool
+        .unclaimed_lp_fees_b
+        .checked_sub(fees_b)
+        .ok_or(FluxDexError::InsufficientFeeBalance)?;
+
+    let clock = Clock::get()?;
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(EmergencyWithdrawn {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        lp_tokens: lp_amount,
+        amount_a,
+        amount_b,
+        fees_a,
+        fees_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Emergency withdraw: {} token A, {} token B, {} LP tokens burned",
+        payout_a,
+        payout_b,
+        lp_amount
+    );
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use crate::instructions::add_concentrated_liquidity::modify_range_liquidity;
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::{CurveType, Pool, TickArray};
+use crate::transfer_tokens_from_pool;
+use crate::utils::ConcentratedMath;
+use crate::FluxDexError;
+use crate::Position;
+use crate::RangeEmergencyWithdrawn;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct EmergencyWithdrawRange<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// 包含区间下界 tick 的 TickArray
+    #[account(
+        mut,
+        constraint = tick_array_lower.pool == pool.key() @ FluxDexError::InvalidAccount,
+    )]
+    pub tick_array_lower: Account<'info, TickArray>,
+
+    /// 包含区间上界 tick 的 TickArray（可与下界相同）
+    #[account(
+        mut,
+        constraint = tick_array_upper.pool == pool.key() @ FluxDexError::InvalidAccount,
+    )]
+    pub tick_array_upper: Account<'info, TickArray>,
+
+    /// 用户在该区间的 Position 账户
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED,
+            user.key().as_ref(),
+            pool.key().as_ref(),
+            &position.tick_lower_index.to_le_bytes(),
+            &position.tick_upper_index.to_le_bytes(),
+        ],
+        bump = position.bump,
+        constraint = position.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+/// 紧急模式下赎回区间头寸的全部流动性，并一并领取累计的手续费
+///
+/// 按当前价格计算取回量，不更新价格观测与动态费率。紧急模式下兑换被拒绝，
+/// 价格不会变化，因此不需要滑点参数
+pub fn emergency_withdraw_range_handler(ctx: Context<EmergencyWithdrawRange>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    if !pool.emergency_mode && !ctx.accounts.global_config.emergency_mode {
+        return Err(FluxDexError::EmergencyModeNotActive.into());
+    }
+    // 闪电贷回调中金库余额尚未归还
+    pool.check_unlocked()?;
+
+    if pool.curve_type != CurveType::Concentrated {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    let liquidity = ctx.accounts.position.liquidity;
+    if liquidity == 0 {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| FluxDexError::Overflow)?;
+
+    // 取回量向下取整
+    let (amount_a, amount_b) = ConcentratedMath::amounts_for_liquidity(
+        pool.current_tick,
+        pool.sqrt_price_x64,
+        ctx.accounts.position.tick_lower_index,
+        ctx.accounts.position.tick_upper_index,
+        liquidity,
+        false,
+    )?;
+    let amount_a = u64::try_from(amount_a).map_err(|_| FluxDexError::Overflow)?;
+    let amount_b = u64::try_from(amount_b).map_err(|_| FluxDexError::Overflow)?;
+
+    // 先结算手续费，再清空头寸流动性
+    modify_range_liquidity(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.position,
+        &mut ctx.accounts.tick_array_lower,
+        &mut ctx.accounts.tick_array_upper,
+        -liquidity_delta,
+    )?;
+
+    let position = &mut ctx.accounts.position;
+    position.record_range_withdrawal(liquidity)?;
+    let fees_a = position.fees_owed_a;
+    let fees_b = position.fees_owed_b;
+    position.fees_owed_a = 0;
+    position.fees_owed_b = 0;
+    position.realized_fees_a = position
+        .realized_fees_a
+        .checked_add(fees_a)
+        .ok_or(FluxDexError::Overflow)?;
+    position.realized_fees_b = position
+        .realized_fees_b
+        .checked_add(fees_b)
+        .ok_or(FluxDexError::Overflow)?;
+
+    let payout_a = amount_a.checked_add(fees_a).ok_or(FluxDexError::Overflow)?;
+    let payout_b = amount_b.checked_add(fees_b).ok_or(FluxDexError::Overflow)?;
+
+    // 执行代币转账：池子 -> 用户
+    if payout_a > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.user_token_a,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+            payout_a,
+        )?;
+    }
+
+    if payout_b > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.user_token_b,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+            payout_b,
+        )?;
+    }
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_sub(amount_a)
+        .ok_or(FluxDexError::Underflow)?;
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_sub(amount_b)
+        .ok_or(FluxDexError::Underflow)?;
+    pool.unclaimed_lp_fees_a = pool
+        .unclaimed_lp_fees_a
+        .checked_sub(fees_a)
+        .ok_or(FluxDexError::InsufficientFeeBalance)?;
+    pool.unclaimed_lp_fees_b = pool
+        .unclaimed_lp_fees_b
+        .checked_sub(fees_b)
+        .ok_or(FluxDexError::InsufficientFeeBalance)?;
+
+    let clock = Clock::get()?;
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(RangeEmergencyWithdrawn {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        tick_lower: ctx.accounts.position.tick_lower_index,
+        tick_upper: ctx.accounts.position.tick_upper_index,
+        liquidity,
+        amount_a,
+        amount_b,
+        fees_a,
+        fees_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Range emergency withdraw: {} token A, {} token B, liquidity {}",
+        payout_a,
+        payout_b,
+        liquidity
+    );
+    Ok(())
+}
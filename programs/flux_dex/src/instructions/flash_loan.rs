@@ -0,0 +1,225 @@
+use crate::state::seeds::*;
+use crate::state::CurveType;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::transfer_tokens_from_pool;
+use crate::FlashLoanExecuted;
+use crate::FluxDexError;
+use crate::MathUtils;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    pub borrower: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// 借出代币的接收账户（可以属于回调程序）
+    #[account(
+        mut,
+        constraint = destination_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub destination_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub destination_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    /// CHECK: 借款回调程序，以 remaining_accounts 和 data 调用，不能是本程序
+    #[account(
+        executable,
+        constraint = receiver_program.key() != crate::ID @ FluxDexError::InvalidAccount,
+    )]
+    pub receiver_program: UncheckedAccount<'info>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+pub fn flash_loan_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+    amount_a: u64, // 借出的 Token A 数量
+    amount_b: u64, // 借出的 Token B 数量
+    data: Vec<u8>, // 回调指令数据，原样传给 receiver_program
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    // 集中流动性池的储备不按份额归属 LP，计入储备的手续费无人可领
+    if pool.curve_type == CurveType::Concentrated {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    if amount_a == 0 && amount_b == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    // 只出借 LP 储备，待提取的协议费与 LP 手续费不外借
+    if amount_a > pool.token_a_reserve || amount_b > pool.token_b_reserve {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    // 手续费按当前费率向上取整，避免小额借款免费
+    let fee_bps = pool.current_fee_bps as u128;
+    let fee_a = MathUtils::mul_div_ceil(amount_a as u128, fee_bps, 10000)? as u64;
+    let fee_b = MathUtils::mul_div_ceil(amount_b as u128, fee_bps, 10000)? as u64;
+
+    let vault_a_before = ctx.accounts.pool_token_a_vault.amount;
+    let vault_b_before = ctx.accounts.pool_token_b_vault.amount;
+
+    // 上锁并立即写回账户数据，回调中重入本池的指令会读到 locked
+    ctx.accounts.pool.locked = true;
+    ctx.accounts.pool.exit(&crate::ID)?;
+
+    // 池子 -> 借款人
+    if amount_a > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.destination_token_a,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+            amount_a,
+        )?;
+    }
+    if amount_b > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.destination_token_b,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+            amount_b,
+        )?;
+    }
+
+    // 调用借款回调（不附带 Pool PDA 签名）
+    let receiver_program = &ctx.accounts.receiver_program;
+    let instruction = Instruction {
+        program_id: receiver_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(receiver_program.to_account_info());
+    invoke(&instruction, &account_infos)?;
+
+    // 回调可能调用了本池未加锁的指令，重新读取最新状态
+    ctx.accounts.pool.reload()?;
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+
+    // 还款检查：金库余额须不低于借款前余额加手续费
+    let required_a = vault_a_before
+        .checked_add(fee_a)
+        .ok_or(FluxDexError::Overflow)?;
+    let required_b = vault_b_before
+        .checked_add(fee_b)
+        .ok_or(FluxDexError::Overflow)?;
+    if ctx.accounts.pool_token_a_vault.amount < required_a
+        || ctx.accounts.pool_token_b_vault.amount < required_b
+    {
+        return Err(FluxDexError::FlashLoanNotRepaid.into());
+    }
+
+    // 储备量变化前记录价格观测
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    ctx.accounts
+        .price_oracle
+        .observe(pool, clock.unix_timestamp)?;
+
+    pool.record_flash_loan(fee_a, fee_b)?;
+    pool.locked = false;
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(FlashLoanExecuted {
+        borrower: ctx.accounts.borrower.key(),
+        pool: ctx.accounts.pool.key(),
+        receiver_program: ctx.accounts.receiver_program.key(),
+        amount_a,
+        amount_b,
+        fee_a,
+        fee_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Flash loan repaid: {} A + {} B borrowed, {} A + {} B fee",
+        amount_a,
+        amount_b,
+        fee_a,
+        fee_b
+    );
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::program::FluxDex;
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(mut)]
+    pub super_admin: Signer<'info>,
+
+    // 只有程序升级权限才能创建全局配置，防止部署后被抢先初始化
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ FluxDexError::InvalidAccount,
+    )]
+    pub program: Program<'info, FluxDex>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(super_admin.key()) @ FluxDexError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    // 全局唯一的配置 PDA
+    #[account(
+        init,
+        payer = super_admin,
+        space = GlobalConfig::SIZE,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_global_config_handler(
+    ctx: Context<InitializeGlobalConfig>,
+    protocol_fee_bps: u16, // 协议从手续费中抽取的比例
+    treasury: Pubkey,      // 协议费接收方
+) -> Result<()> {
+    // 协议费是手续费的一部分，不能超过 100%
+    if protocol_fee_bps > 10000 {
+        return Err(FluxDexError::InvalidFeeTier.into());
+    }
+
+    let config = &mut ctx.accounts.global_config;
+
+    config.super_admin = ctx.accounts.super_admin.key();
+
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.treasury = treasury;
+
+    // ALM 与 MEV 保护默认关闭，由超级管理员显式开启
+    config.alm_enabled_globally = false;
+    config.mev_protection_enabled_globally = false;
+    config.cross_chain_enabled = false;
+    config.emergency_mode = false;
+
+    config.version = 1;
+    config.upgrade_authority = ctx.accounts.super_admin.key();
+
+    config.total_pools = 0;
+    config.total_volume = 0;
+    config.total_fees_collected = 0;
+
+    let clock = Clock::get()?;
+    config.created_at = clock.unix_timestamp;
+    config.bump = ctx.bumps.global_config;
+
+    Ok(())
+}
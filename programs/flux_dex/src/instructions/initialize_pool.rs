@@ -1,28 +1,59 @@
 use crate::state::seeds::*;
+use crate::state::CurveType;
+use crate::state::GlobalConfig;
 use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::state::MAX_FEE_BPS;
+use crate::state::MAX_OBSERVATIONS;
+use crate::state::{MAX_AMP, MAX_TICK_SPACING, MIN_AMP, MIN_WEIGHT_BPS};
+use crate::utils::{lp_token_name_and_symbol, token_symbol};
+use crate::utils::{validate_mint_extensions, ConcentratedMath};
+use crate::FluxDexError;
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
-use anchor_spl::token::Token;
-use anchor_spl::token::TokenAccount;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3, Metadata};
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
 
 #[derive(Accounts)]
+#[instruction(base_fee_bps: u16, curve_type: CurveType)]
 pub struct InitializePool<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    // Pool state PDA
+    // Token mints (must exist before calling initialize_pool)
+    // 要求 token_a_mint < token_b_mint，保证同一交易对只有一个规范地址
+    #[account(
+        constraint = token_a_mint.key() < token_b_mint.key() @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_b_program)]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    // Pool state PDA：由排序后的交易对、费率档位和定价曲线派生
     #[account(
         init,
         payer = authority,
         space = Pool::SIZE,
-        seeds = [POOL_SEED, authority.key().as_ref()],
+        seeds = [
+            POOL_SEED,
+            token_a_mint.key().as_ref(),
+            token_b_mint.key().as_ref(),
+            &base_fee_bps.to_le_bytes(),
+            &[curve_type as u8],
+        ],
         bump,
     )]
     pub pool: Account<'info, Pool>,
 
-    // Token mints (must exist before calling initialize_pool)
-    pub token_a_mint: Account<'info, Mint>,
-    pub token_b_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
 
     // Token vaults owned by the pool PDA
     #[account(
@@ -30,38 +61,150 @@ pub struct InitializePool<'info> {
         payer = authority,
         token::mint = token_a_mint,
         token::authority = pool,
+        token::token_program = token_a_program,
         seeds = [TOKEN_A_VAULT_SEED,pool.key().as_ref()],
         bump
     )]
-    pub token_a_vault: Account<'info, TokenAccount>,
+    pub token_a_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init,
         payer = authority,
         token::mint = token_b_mint,
         token::authority = pool,
+        token::token_program = token_b_program,
         seeds = [TOKEN_B_VAULT_SEED,pool.key().as_ref()],
         bump
     )]
-    pub token_b_vault: Account<'info, TokenAccount>,
+    pub token_b_vault: InterfaceAccount<'info, TokenAccount>,
 
-    // LP token mint
+    // LP token mint：Pool PDA 同时是冻结权限，持有 LP 的账户保持冻结，LP 不可转让
     #[account(
         init,
         payer = authority,
         mint::decimals = 6,
         mint::authority = pool,
+        mint::freeze_authority = pool,
         seeds = [LP_MINT_SEED,pool.key().as_ref()],
         bump,
     )]
-    pub lp_mint: Account<'info, Mint>,
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: LP 代币的 Metaplex 元数据账户，由 Token Metadata 程序创建并校验
+    #[account(
+        mut,
+        seeds = [METADATA_SEED, token_metadata_program.key().as_ref(), lp_mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub lp_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Token A 的 Metaplex 元数据账户，仅用于读取符号，可以尚未创建
+    #[account(
+        seeds = [METADATA_SEED, token_metadata_program.key().as_ref(), token_a_mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub token_a_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Token B 的 Metaplex 元数据账户，仅用于读取符号，可以尚未创建
+    #[account(
+        seeds = [METADATA_SEED, token_metadata_program.key().as_ref(), token_b_mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub token_b_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: 永久锁定 LP 的所有者，程序从不以该 PDA 签名
+    #[account(
+        seeds = [LOCKED_LP_AUTHORITY_SEED, pool.key().as_ref()],
+        bump,
+    )]
+    pub locked_lp_authority: UncheckedAccount<'info>,
+
+    // 首次存入时锁定 MIN_LIQUIDITY_THRESHOLD 个 LP 的账户
+    #[account(
+        init,
+        payer = authority,
+        token::mint = lp_mint,
+        token::authority = locked_lp_authority,
+        seeds = [LOCKED_LP_SEED, pool.key().as_ref()],
+        bump,
+    )]
+    pub locked_lp_token: InterfaceAccount<'info, TokenAccount>,
+
+    // 价格观测环形缓冲区
+    #[account(
+        init,
+        payer = authority,
+        space = PriceObservations::SIZE,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// LP 代币所属的代币程序
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metadata>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn initialize_pool_handler(ctx: Context<InitializePool>, base_fee_bps: u16) -> Result<()> {
+pub fn initialize_pool_handler(
+    ctx: Context<InitializePool>,
+    base_fee_bps: u16,            // 基础费率，同时作为费率档位
+    curve_type: CurveType,        // 定价曲线
+    amp: u64,                     // StableSwap 放大系数，其他曲线忽略
+    weight_a_bps: u16,            // 加权池 Token A 权重，其他曲线忽略
+    tick_spacing: u16,            // 集中流动性 tick 间距，其他曲线忽略
+    initial_sqrt_price_x64: u128, // 集中流动性初始价格平方根（Q64.64），其他曲线忽略
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+
+    if base_fee_bps > MAX_FEE_BPS {
+        return Err(FluxDexError::InvalidFeeTier.into());
+    }
+
+    validate_mint_extensions(&ctx.accounts.token_a_mint)?;
+    validate_mint_extensions(&ctx.accounts.token_b_mint)?;
+
+    let (amp, weight_a_bps) = match curve_type {
+        CurveType::ConstantProduct => (0, 5000),
+        CurveType::StableSwap => {
+            if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+                return Err(FluxDexError::InvalidAmplification.into());
+            }
+            // 不变式按原始数量计算，两侧精度不同时挂钩价格会偏离 10 的幂次倍
+            if ctx.accounts.token_a_mint.decimals != ctx.accounts.token_b_mint.decimals {
+                return Err(FluxDexError::MintDecimalsMismatch.into());
+            }
+            (amp, 5000)
+        }
+        CurveType::Weighted => {
+            if !(MIN_WEIGHT_BPS..=10000 - MIN_WEIGHT_BPS).contains(&weight_a_bps) {
+                return Err(FluxDexError::InvalidWeight.into());
+            }
+            (0, weight_a_bps)
+        }
+        CurveType::Concentrated => {
+            if tick_spacing == 0 || tick_spacing > MAX_TICK_SPACING {
+                return Err(FluxDexError::InvalidTickSpacing.into());
+            }
+            (0, 5000)
+        }
+    };
+
+    let (tick_spacing, sqrt_price_x64, current_tick) = if curve_type == CurveType::Concentrated {
+        let current_tick = ConcentratedMath::tick_at_sqrt_price(initial_sqrt_price_x64)?;
+        (tick_spacing, initial_sqrt_price_x64, current_tick)
+    } else {
+        (0, 0, 0)
+    };
+
     let pool = &mut ctx.accounts.pool;
 
     pool.authority = ctx.accounts.authority.key();
@@ -75,14 +218,29 @@ pub fn initialize_pool_handler(ctx: Context<InitializePool>, base_fee_bps: u16)
     pool.token_b_reserve = 0;
     pool.lp_supply = 0;
 
+    pool.fee_tier = base_fee_bps;
     pool.base_fee_bps = base_fee_bps; // 默认基础费率 0.3%
     pool.dynamic_fee_enabled = false;
     pool.current_fee_bps = base_fee_bps;
 
+    pool.curve_type = curve_type;
+    pool.amp_initial = amp;
+    pool.amp_target = amp;
+    pool.amp_ramp_start_ts = 0;
+    pool.amp_ramp_stop_ts = 0;
+    pool.weight_a_bps = weight_a_bps;
+    pool.weight_b_bps = 10000 - weight_a_bps;
+    pool.sqrt_price_x64 = sqrt_price_x64;
+    pool.current_tick = current_tick;
+    pool.tick_spacing = tick_spacing;
+    pool.liquidity = 0;
+
     pool.alm_enabled = false;
     pool.volatility_score = 0;
     pool.last_price_update = 0;
-    pool.price_oracle = Pubkey::default();
+    pool.price_oracle = ctx.accounts.price_oracle.key();
+    pool.price_a_cumulative = 0;
+    pool.price_b_cumulative = 0;
 
     pool.mev_protection_enabled = false;
     pool.intent_pool = Pubkey::default();
@@ -94,10 +252,22 @@ pub fn initialize_pool_handler(ctx: Context<InitializePool>, base_fee_bps: u16)
     pool.total_fees_collected_a = 0;
     pool.total_fees_collected_b = 0;
     pool.swap_count = 0;
+    pool.flash_loan_count = 0;
+
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    pool.fee_growth_global_a = 0;
+    pool.fee_growth_global_b = 0;
+    pool.unclaimed_lp_fees_a = 0;
+    pool.unclaimed_lp_fees_b = 0;
 
     pool.paused = false;
     pool.emergency_mode = false;
     pool.upgrade_authority = ctx.accounts.authority.key();
+    pool.pending_authority = Pubkey::default();
+    pool.pending_upgrade_authority = Pubkey::default();
+    pool.locked = false;
 
     let clock = Clock::get()?;
     pool.created_at = clock.unix_timestamp;
@@ -105,5 +275,71 @@ pub fn initialize_pool_handler(ctx: Context<InitializePool>, base_fee_bps: u16)
 
     pool.bump = ctx.bumps.pool;
 
+    let price_oracle = &mut ctx.accounts.price_oracle;
+    price_oracle.pool = pool.key();
+    price_oracle.index = 0;
+    price_oracle.observations = Vec::with_capacity(MAX_OBSERVATIONS);
+    price_oracle.bump = ctx.bumps.price_oracle;
+
+    let global_config = &mut ctx.accounts.global_config;
+    global_config.total_pools = global_config
+        .total_pools
+        .checked_add(1)
+        .ok_or(FluxDexError::Overflow)?;
+
+    create_lp_metadata(&ctx)?;
+
     Ok(())
 }
+
+/// 为 LP mint 创建 Metaplex 元数据（由 Pool PDA 作为铸造权限和更新权限签名）
+///
+/// URI 初始为空，由池子管理员通过 update_lp_metadata_uri 设置
+fn create_lp_metadata(ctx: &Context<InitializePool>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let symbol_a = token_symbol(
+        &accounts.token_a_mint.key(),
+        &accounts.token_a_metadata.to_account_info(),
+    );
+    let symbol_b = token_symbol(
+        &accounts.token_b_mint.key(),
+        &accounts.token_b_metadata.to_account_info(),
+    );
+    let (name, symbol) = lp_token_name_and_symbol(&symbol_a, &symbol_b);
+
+    let pool = &accounts.pool;
+    let fee_tier = pool.fee_tier.to_le_bytes();
+    let curve_type = [pool.curve_type as u8];
+    let authority_seed = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &fee_tier[..],
+        &curve_type[..],
+        &[pool.bump],
+    ];
+    let signer = &[&authority_seed[..]];
+
+    let cpi_accounts = CreateMetadataAccountsV3 {
+        metadata: accounts.lp_metadata.to_account_info(),
+        mint: accounts.lp_mint.to_account_info(),
+        mint_authority: pool.to_account_info(),
+        payer: accounts.authority.to_account_info(),
+        update_authority: pool.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        rent: accounts.rent.to_account_info(),
+    };
+    let cpi_program = accounts.token_metadata_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+    let data = DataV2 {
+        name,
+        symbol,
+        uri: String::new(),
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+    create_metadata_accounts_v3(cpi_ctx, data, true, true, None)
+}
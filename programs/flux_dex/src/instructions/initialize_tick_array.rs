@@ -0,0 +1,67 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::{CurveType, Pool, Tick, TickArray, MAX_TICK, MIN_TICK, TICK_ARRAY_SIZE};
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TickArray::SIZE,
+        seeds = [TICK_ARRAY_SEED, pool.key().as_ref(), &start_tick_index.to_le_bytes()],
+        bump,
+    )]
+    pub tick_array: Account<'info, TickArray>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 为集中流动性池创建 TickArray，任何人都可以支付租金创建
+pub fn initialize_tick_array_handler(
+    ctx: Context<InitializeTickArray>,
+    start_tick_index: i32, // 数组起始 tick，须按 tick_spacing * TICK_ARRAY_SIZE 对齐
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let pool = &ctx.accounts.pool;
+
+    if pool.curve_type != CurveType::Concentrated {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    let span = TickArray::span(pool.tick_spacing);
+    if start_tick_index % span != 0 {
+        return Err(FluxDexError::InvalidTickSpacing.into());
+    }
+    if start_tick_index + span <= MIN_TICK || start_tick_index > MAX_TICK {
+        return Err(FluxDexError::TickOutOfRange.into());
+    }
+
+    let tick_array = &mut ctx.accounts.tick_array;
+    tick_array.pool = pool.key();
+    tick_array.start_tick_index = start_tick_index;
+    tick_array.ticks = vec![Tick::default(); TICK_ARRAY_SIZE];
+    tick_array.bump = ctx.bumps.tick_array;
+
+    msg!("✅ Tick array initialized at tick {}", start_tick_index);
+    Ok(())
+}
@@ -1,5 +1,69 @@
+pub mod accept_authority;
+pub mod add_concentrated_liquidity;
 pub mod add_liquidity;
+pub mod add_liquidity_single_sided;
+pub mod cancel_authority_transfer;
+pub mod close_position;
+pub mod collect_fees;
+pub mod collect_protocol_fees;
+pub mod consult;
+pub mod emergency_withdraw;
+pub mod emergency_withdraw_range;
+pub mod flash_loan;
+pub mod initialize_global_config;
 pub mod initialize_pool;
+pub mod initialize_tick_array;
+pub mod propose_authority;
+pub mod ramp_amp;
+pub mod remove_concentrated_liquidity;
+pub mod remove_liquidity;
+pub mod remove_liquidity_single_sided;
+pub mod route_swap;
+pub mod set_global_flag;
+pub mod set_pool_flag;
+pub mod skim;
+pub mod stop_ramp_amp;
+pub mod swap;
+pub mod swap_exact_out;
+pub mod sync;
+pub mod update_base_fee;
+pub mod update_lp_metadata_uri;
+pub mod update_protocol_fee;
+pub mod update_super_admin;
+pub mod update_treasury;
+pub mod update_upgrade_authority;
 
+pub use accept_authority::*;
+pub use add_concentrated_liquidity::*;
 pub use add_liquidity::*;
+pub use add_liquidity_single_sided::*;
+pub use cancel_authority_transfer::*;
+pub use close_position::*;
+pub use collect_fees::*;
+pub use collect_protocol_fees::*;
+pub use consult::*;
+pub use emergency_withdraw::*;
+pub use emergency_withdraw_range::*;
+pub use flash_loan::*;
+pub use initialize_global_config::*;
 pub use initialize_pool::*;
+pub use initialize_tick_array::*;
+pub use propose_authority::*;
+pub use ramp_amp::*;
+pub use remove_concentrated_liquidity::*;
+pub use remove_liquidity::*;
+pub use remove_liquidity_single_sided::*;
+pub use route_swap::*;
+pub use set_global_flag::*;
+pub use set_pool_flag::*;
+pub use skim::*;
+pub use stop_ramp_amp::*;
+pub use swap::*;
+pub use swap_exact_out::*;
+pub use sync::*;
+pub use update_base_fee::*;
+pub use update_lp_metadata_uri::*;
+pub use update_protocol_fee::*;
+pub use update_super_admin::*;
+pub use update_treasury::*;
+pub use update_upgrade_authority::*;
@@ -0,0 +1,69 @@
+use crate::state::seeds::*;
+use crate::state::AuthorityRole;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::AuthorityTransferProposed;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct TransferPoolAuthority<'info> {
+    /// 提议与取消时为角色当前持有者，接受时为待接受者
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// 提议将角色移交给 new_holder，需由 new_holder 调用 accept_authority 完成
+///
+/// 再次提议会覆盖尚未接受的提议
+pub fn propose_authority_handler(
+    ctx: Context<TransferPoolAuthority>,
+    role: AuthorityRole, // 要移交的角色
+    new_holder: Pubkey,  // 新的持有者
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let pool = &mut ctx.accounts.pool;
+    let current = pool.role_holder(role);
+
+    if ctx.accounts.signer.key() != current {
+        return Err(FluxDexError::Unauthorized.into());
+    }
+
+    if new_holder == Pubkey::default() || new_holder == current {
+        return Err(FluxDexError::InvalidAccount.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    pool.set_pending_role_holder(role, new_holder);
+    pool.updated_at = now;
+
+    emit!(AuthorityTransferProposed {
+        pool: pool.key(),
+        role,
+        current,
+        pending: new_holder,
+        timestamp: now,
+    });
+
+    msg!(
+        "✅ {:?} transfer proposed: {} -> {}",
+        role,
+        current,
+        new_holder
+    );
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::state::seeds::*;
+use crate::state::CurveType;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION};
+use crate::AmpRampStarted;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump,
+        has_one = authority @ FluxDexError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+pub fn ramp_amp_handler(
+    ctx: Context<RampAmp>,
+    target_amp: u64,   // 目标放大系数
+    ramp_stop_ts: i64, // 调整结束时间
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let pool = &mut ctx.accounts.pool;
+
+    if pool.curve_type != CurveType::StableSwap {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+
+    // 上一次调整结束前不允许重新开始，且调整周期不能太短
+    if now < pool.amp_ramp_stop_ts || ramp_stop_ts < now.saturating_add(MIN_RAMP_DURATION) {
+        return Err(FluxDexError::InvalidAmplification.into());
+    }
+
+    if !(MIN_AMP..=MAX_AMP).contains(&target_amp) {
+        return Err(FluxDexError::InvalidAmplification.into());
+    }
+
+    // 单次调整幅度不超过 MAX_AMP_CHANGE 倍
+    let current_amp = pool.current_amp(now);
+    let exceeds_max_change = if target_amp >= current_amp {
+        target_amp > current_amp.saturating_mul(MAX_AMP_CHANGE)
+    } else {
+        target_amp.saturating_mul(MAX_AMP_CHANGE) < current_amp
+    };
+    if exceeds_max_change {
+        return Err(FluxDexError::InvalidAmplification.into());
+    }
+
+    pool.amp_initial = current_amp;
+    pool.amp_target = target_amp;
+    pool.amp_ramp_start_ts = now;
+    pool.amp_ramp_stop_ts = ramp_stop_ts;
+    pool.updated_at = now;
+
+    emit!(AmpRampStarted {
+        pool: pool.key(),
+        old_amp: current_amp,
+        target_amp,
+        ramp_start_ts: now,
+        ramp_stop_ts,
+    });
+
+    msg!(
+        "✅ Amp ramp started: {} -> {} until {}",
+        current_amp,
+        target_amp,
+        ramp_stop_ts
+    );
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use crate::instructions::add_concentrated_liquidity::modify_range_liquidity;
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::{CurveType, Pool, PriceObservations, TickArray};
+use crate::utils::ConcentratedMath;
+use crate::FluxDexError;
+use crate::Position;
+use crate::RangeLiquidityRemoved;
+use crate::{transfer_fee_amount, transfer_tokens_from_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct RemoveConcentratedLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// 包含区间下界 tick 的 TickArray
+    #[account(
+        mut,
+        constraint = tick_array_lower.pool == pool.key() @ FluxDexError::InvalidAccount,
+    )]
+    pub tick_array_lower: Account<'info, TickArray>,
+
+    /// 包含区间上界 tick 的 TickArray（可与下界相同）
+    #[account(
+        mut,
+        constraint = tick_array_upper.pool == pool.key() @ FluxDexError::InvalidAccount,
+    )]
+    pub tick_array_upper: Account<'info, TickArray>,
+
+    /// 用户在该区间的 Position 账户
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED,
+            user.key().as_ref(),
+            pool.key().as_ref(),
+            &position.tick_lower_index.to_le_bytes(),
+            &position.tick_upper_index.to_le_bytes(),
+        ],
+        bump = position.bump,
+        constraint = position.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+/// 赎回区间流动性；liquidity 为 0 时只结算手续费，随后可通过 collect_fees 领取
+pub fn remove_concentrated_liquidity_handler(
+    ctx: Context<RemoveConcentratedLiquidity>,
+    liquidity: u128,   // 要赎回的流动性
+    min_amount_a: u64, // 最少取回的 Token A 数量（滑点保护）
+    min_amount_b: u64, // 最少取回的 Token B 数量（滑点保护）
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let position = &ctx.accounts.position;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    if pool.curve_type != CurveType::Concentrated {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    if position.liquidity == 0 || position.liquidity < liquidity {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| FluxDexError::Overflow)?;
+
+    // 取回量向下取整
+    let (amount_a, amount_b) = ConcentratedMath::amounts_for_liquidity(
+        pool.current_tick,
+        pool.sqrt_price_x64,
+        position.tick_lower_index,
+        position.tick_upper_index,
+        liquidity,
+        false,
+    )?;
+    let amount_a = u64::try_from(amount_a).map_err(|_| FluxDexError::Overflow)?;
+    let amount_b = u64::try_from(amount_b).map_err(|_| FluxDexError::Overflow)?;
+
+    // 检查滑点保护：按扣除转账手续费后用户实际到账的数量
+    let net_amount_a =
+        amount_a.saturating_sub(transfer_fee_amount(&ctx.accounts.token_a_mint, amount_a)?);
+    let net_amount_b =
+        amount_b.saturating_sub(transfer_fee_amount(&ctx.accounts.token_b_mint, amount_b)?);
+    if net_amount_a < min_amount_a || net_amount_b < min_amount_b {
+        return Err(FluxDexError::SlippageExceeded.into());
+    }
+
+    // 执行代币转账：池子 -> 用户
+    transfer_tokens_from_pool(
+        &ctx.accounts.pool,
+        &ctx.accounts.pool_token_a_vault,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program,
+        amount_a,
+    )?;
+
+    transfer_tokens_from_pool(
+        &ctx.accounts.pool,
+        &ctx.accounts.pool_token_b_vault,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program,
+        amount_b,
+    )?;
+
+    // 储备量变化前记录价格观测
+    let clock = Clock::get()?;
+    ctx.accounts
+        .price_oracle
+        .observe(&mut ctx.accounts.pool, clock.unix_timestamp)?;
+
+    // 先结算手续费，再减少头寸流动性
+    modify_range_liquidity(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.position,
+        &mut ctx.accounts.tick_array_lower,
+        &mut ctx.accounts.tick_array_upper,
+        -liquidity_delta,
+    )?;
+    ctx.accounts.position.record_range_withdrawal(liquidity)?;
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_sub(amount_a)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_sub(amount_b)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(RangeLiquidityRemoved {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        tick_lower: ctx.accounts.position.tick_lower_index,
+        tick_upper: ctx.accounts.position.tick_upper_index,
+        liquidity,
+        amount_a,
+        amount_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Range liquidity removed: {} token A, {} token B, liquidity {}",
+        amount_a,
+        amount_b,
+        liquidity
+    );
+    Ok(())
+}
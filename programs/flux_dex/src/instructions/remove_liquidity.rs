@@ -0,0 +1,225 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::FluxDexError;
+use crate::LiquidityRemoved;
+use crate::Position;
+use crate::{burn_lp_tokens, transfer_fee_amount, transfer_tokens_from_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token.mint == pool.lp_mint @ FluxDexError::InvalidAccount,
+        constraint = user_lp_token.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_lp_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// 用户的 Position 账户
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, user.key().as_ref(), pool.key().as_ref()],
+        bump = position.bump,
+        constraint = position.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// LP 代币所属的代币程序
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn remove_liquidity_handler(
+    ctx: Context<RemoveLiquidity>,
+    lp_amount: u64,    // 要赎回的 LP 代币数量
+    min_amount_a: u64, // 最少取回的 Token A 数量（滑点保护）
+    min_amount_b: u64, // 最少取回的 Token B 数量（滑点保护）
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    if lp_amount == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    if ctx.accounts.user_lp_token.amount < lp_amount || ctx.accounts.position.lp_tokens < lp_amount
+    {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    // 按池子曲线计算可取回的资产
+    let clock = Clock::get()?;
+    let (amount_a, amount_b) = pool.calculate_withdraw_amounts(lp_amount, clock.unix_timestamp)?;
+
+    // 检查滑点保护：按扣除转账手续费后用户实际到账的数量
+    let net_amount_a =
+        amount_a.saturating_sub(transfer_fee_amount(&ctx.accounts.token_a_mint, amount_a)?);
+    let net_amount_b =
+        amount_b.saturating_sub(transfer_fee_amount(&ctx.accounts.token_b_mint, amount_b)?);
+    if net_amount_a < min_amount_a || net_amount_b < min_amount_b {
+        return Err(FluxDexError::SlippageExceeded.into());
+    }
+
+    // 销毁用户 LP 代币
+    burn_lp_tokens(
+        &ctx.accounts.pool,
+        &ctx.accounts.user,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp_token,
+        &ctx.accounts.token_program,
+        lp_amount,
+    )?;
+
+    // 执行代币转账：池子 -> 用户
+    transfer_tokens_from_pool(
+        &ctx.accounts.pool,
+        &ctx.accounts.pool_token_a_vault,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.token_a_mint,
+        &ctx.accounts.token_a_program,
+        amount_a,
+    )?;
+
+    transfer_tokens_from_pool(
+        &ctx.accounts.pool,
+        &ctx.accounts.pool_token_b_vault,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.token_b_mint,
+        &ctx.accounts.token_b_program,
+        amount_b,
+    )?;
+
+    // 更新池子状态
+    let pool = &mut ctx.accounts.pool;
+    // 储备量变化前记录价格观测
+    ctx.accounts
+        .price_oracle
+        .observe(pool, clock.unix_timestamp)?;
+
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_sub(amount_a)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_sub(amount_b)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.lp_supply = pool
+        .lp_supply
+        .checked_sub(lp_amount)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    // 更新用户 Position（先结算手续费）
+    ctx.accounts.position.accrue_fees(&ctx.accounts.pool)?;
+    ctx.accounts.position.record_withdrawal(lp_amount)?;
+
+    // 发出事件
+    emit!(LiquidityRemoved {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        amount_a,
+        amount_b,
+        lp_tokens: lp_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Liquidity removed: {} token A, {} token B, {} LP tokens burned",
+        amount_a,
+        amount_b,
+        lp_amount
+    );
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use crate::instructions::swap::update_dynamic_fee;
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::FluxDexError;
+use crate::LiquidityRemoved;
+use crate::MathUtils;
+use crate::Position;
+use crate::{burn_lp_tokens, transfer_fee_amount, transfer_tokens_from_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct RemoveLiquiditySingleSided<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// 用户接收的代币账户（Token A 或 Token B）
+    #[account(
+        mut,
+        constraint = (user_token_out.mint == pool.token_a_mint || user_token_out.mint == pool.token_b_mint) @ FluxDexError::InvalidAccount,
+        constraint = user_token_out.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_out: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token.mint == pool.lp_mint @ FluxDexError::InvalidAccount,
+        constraint = user_lp_token.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_lp_token: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, user.key().as_ref(), pool.key().as_ref()],
+        bump = position.bump,
+        constraint = position.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+
+    /// LP 代币所属的代币程序
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn remove_liquidity_single_sided_handler(
+    ctx: Context<RemoveLiquiditySingleSided>,
+    lp_amount: u64,      // 要赎回的 LP 代币数量
+    min_amount_out: u64, // 最少取回的单边代币数量（滑点保护）
+) -> Result<()> {
+    // 在池内兑换改变手续费增长之前结算 Position 手续费
+    ctx.accounts.position.accrue_fees(&ctx.accounts.pool)?;
+
+    let pool = &mut ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    if lp_amount == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    if ctx.accounts.user_lp_token.amount < lp_amount || ctx.accounts.position.lp_tokens < lp_amount
+    {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    // 按池子曲线计算可取回的资产
+    let clock = Clock::get()?;
+    let (amount_a, amount_b) = pool.calculate_withdraw_amounts(lp_amount, clock.unix_timestamp)?;
+
+    // 储备量变化前记录价格观测
+    ctx.accounts
+        .price_oracle
+        .observe(pool, clock.unix_timestamp)?;
+    let pre_swap_price = pool.get_current_price(clock.unix_timestamp);
+
+    pool.token_a_reserve = pool
+        .token_a_reserve
+        .checked_sub(amount_a)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.token_b_reserve = pool
+        .token_b_reserve
+        .checked_sub(amount_b)
+        .ok_or(FluxDexError::Underflow)?;
+
+    pool.lp_supply = pool
+        .lp_supply
+        .checked_sub(lp_amount)
+        .ok_or(FluxDexError::Underflow)?;
+
+    // 将不需要的一侧通过池子曲线兑换回目标代币
+    let out_is_a = ctx.accounts.user_token_out.mint == pool.token_a_mint;
+    let (direct_amount, swap_amount) = if out_is_a {
+        (amount_a, amount_b)
+    } else {
+        (amount_b, amount_a)
+    };
+
+    let fee_bps = pool.current_fee_bps;
+    let protocol_fee_bps = ctx.accounts.global_config.protocol_fee_bps;
+    let swap_output = if swap_amount > 0 {
+        let output =
+            pool.calculate_swap_output(swap_amount, !out_is_a, fee_bps, clock.unix_timestamp)?;
+        let fee_amount = MathUtils::calculate_fee_amount(swap_amount, fee_bps)?;
+        pool.record_swap(!out_is_a, swap_amount, output, fee_amount, protocol_fee_bps)?;
+        output
+    } else {
+        0
+    };
+
+    let amount_out = direct_amount
+        .checked_add(swap_output)
+        .ok_or(FluxDexError::Overflow)?;
+
+    // 检查滑点保护：按扣除转账手续费后用户实际到账的数量
+    let mint_out = if out_is_a {
+        &ctx.accounts.token_a_mint
+    } else {
+        &ctx.accounts.token_b_mint
+    };
+    let net_amount_out = amount_out.saturating_sub(transfer_fee_amount(mint_out, amount_out)?);
+    if amount_out == 0 || net_amount_out < min_amount_out {
+        return Err(FluxDexError::MinimumOutputNotMet.into());
+    }
+
+    // 销毁用户 LP 代币
+    burn_lp_tokens(
+        &ctx.accounts.pool,
+        &ctx.accounts.user,
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.user_lp_token,
+        &ctx.accounts.token_program,
+        lp_amount,
+    )?;
+
+    // 执行代币转账：池子 -> 用户
+    let (pool_vault, mint_out, token_program_out) = if out_is_a {
+        (
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+        )
+    } else {
+        (
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+        )
+    };
+    transfer_tokens_from_pool(
+        &ctx.accounts.pool,
+        pool_vault,
+        &ctx.accounts.user_token_out,
+        mint_out,
+        token_program_out,
+        amount_out,
+    )?;
+
+    update_dynamic_fee(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.price_oracle,
+        pre_swap_price,
+        clock.unix_timestamp,
+    )?;
+    ctx.accounts.pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    // 更新用户 Position
+    ctx.accounts.position.record_withdrawal(lp_amount)?;
+
+    let (paid_a, paid_b) = if out_is_a {
+        (amount_out, 0)
+    } else {
+        (0, amount_out)
+    };
+    emit!(LiquidityRemoved {
+        user: ctx.accounts.user.key(),
+        pool: ctx.accounts.pool.key(),
+        amount_a: paid_a,
+        amount_b: paid_b,
+        lp_tokens: lp_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Single-sided liquidity removed: {} out ({} swapped), {} LP tokens burned",
+        amount_out,
+        swap_amount,
+        lp_amount
+    );
+    Ok(())
+}
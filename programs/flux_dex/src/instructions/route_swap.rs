@@ -0,0 +1,314 @@
+use crate::instructions::swap::update_dynamic_fee;
+use crate::state::seeds::*;
+use crate::state::CurveType;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::state::MAX_ROUTE_HOPS;
+use crate::FluxDexError;
+use crate::MathUtils;
+use crate::RouteHopExecuted;
+use crate::{transfer_fee_amount, transfer_tokens_from_pool, transfer_tokens_to_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+/// 每一跳在 remaining_accounts 中占用的账户数：
+/// pool, vault_in, vault_out, mint_out, token_program_out, price_oracle
+const ACCOUNTS_PER_HOP: usize = 6;
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// 用户的输入代币账户（第一跳的输入）
+    #[account(
+        mut,
+        constraint = user_token_in.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_in: InterfaceAccount<'info, TokenAccount>,
+
+    /// 用户的输出代币账户（最后一跳的输出）
+    #[account(
+        mut,
+        constraint = user_token_out.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_out: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = user_token_in.mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_in_program,
+    )]
+    pub token_in_mint: InterfaceAccount<'info, Mint>,
+
+    /// 输入代币所属的代币程序（SPL Token 或 Token-2022）
+    pub token_in_program: Interface<'info, TokenInterface>,
+}
+
+/// 路由中的一跳，账户均已校验
+struct RouteHop<'info> {
+    pool: Account<'info, Pool>,
+    vault_in: InterfaceAccount<'info, TokenAccount>,
+    vault_out: InterfaceAccount<'info, TokenAccount>,
+    mint_out: InterfaceAccount<'info, Mint>,
+    token_program_out: Interface<'info, TokenInterface>,
+    price_oracle: Account<'info, PriceObservations>,
+    a_to_b: bool,
+}
+
+pub fn route_swap_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+    amount_in: u64,      // 第一跳的输入数量
+    min_amount_out: u64, // 最后一跳最少获得的输出数量（端到端滑点保护）
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+
+    if amount_in == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    if ctx.accounts.user_token_in.amount < amount_in {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    let mut hops = load_route(
+        ctx.remaining_accounts,
+        ctx.accounts.user_token_in.mint,
+        ctx.accounts.user_token_out.mint,
+    )?;
+
+    // 用户 -> 第一跳金库，按实际到账数量开始路由
+    let mut hop_amount_in = transfer_tokens_to_pool(
+        &ctx.accounts.user,
+        &ctx.accounts.user_token_in,
+        &mut hops[0].vault_in,
+        &ctx.accounts.token_in_mint,
+        &ctx.accounts.token_in_program,
+        amount_in,
+    )?;
+
+    let protocol_fee_bps = ctx.accounts.global_config.protocol_fee_bps;
+    let clock = Clock::get()?;
+    let mut amount_out = 0;
+
+    for index in 0..hops.len() {
+        let (current, rest) = hops[index..].split_first_mut().unwrap();
+        let fee_bps = current.pool.current_fee_bps;
+        amount_out = current.pool.calculate_swap_output(
+            hop_amount_in,
+            current.a_to_b,
+            fee_bps,
+            clock.unix_timestamp,
+        )?;
+        if amount_out == 0 {
+            return Err(FluxDexError::MinimumOutputNotMet.into());
+        }
+
+        // 中间跳直接转入下一跳的金库，最后一跳转给用户
+        let next_amount_in = match rest.first_mut() {
+            Some(next) => {
+                next.vault_in.reload()?;
+                let balance_before = next.vault_in.amount;
+                transfer_tokens_from_pool(
+                    &current.pool,
+                    &current.vault_out,
+                    &next.vault_in,
+                    &current.mint_out,
+                    &current.token_program_out,
+                    amount_out,
+                )?;
+                next.vault_in.reload()?;
+                next.vault_in
+                    .amount
+                    .checked_sub(balance_before)
+                    .ok_or(FluxDexError::Underflow)?
+            }
+            None => {
+                transfer_tokens_from_pool(
+                    &current.pool,
+                    &current.vault_out,
+                    &ctx.accounts.user_token_out,
+                    &current.mint_out,
+                    &current.token_program_out,
+                    amount_out,
+                )?;
+                0
+            }
+        };
+
+        settle_hop(
+            current,
+            ctx.accounts.user.key(),
+            index as u8,
+            hop_amount_in,
+            amount_out,
+            fee_bps,
+            protocol_fee_bps,
+            clock.unix_timestamp,
+        )?;
+        hop_amount_in = next_amount_in;
+    }
+
+    // 端到端滑点保护按扣除转账手续费后用户实际到账的数量
+    let last_mint_out = &hops[hops.len() - 1].mint_out;
+    let net_amount_out = amount_out.saturating_sub(transfer_fee_amount(last_mint_out, amount_out)?);
+    if net_amount_out < min_amount_out {
+        return Err(FluxDexError::MinimumOutputNotMet.into());
+    }
+
+    msg!(
+        "✅ Route swap executed: {} in, {} out over {} hops",
+        amount_in,
+        amount_out,
+        hops.len()
+    );
+    Ok(())
+}
+
+/// 解析并校验 remaining_accounts 中的路由：每一跳的输入代币须为上一跳的输出代币
+fn load_route<'info>(
+    remaining_accounts: &'info [AccountInfo<'info>],
+    mint_in: Pubkey,
+    mint_out: Pubkey,
+) -> Result<Vec<RouteHop<'info>>> {
+    let hop_count = remaining_accounts.len() / ACCOUNTS_PER_HOP;
+    if hop_count == 0
+        || hop_count > MAX_ROUTE_HOPS
+        || hop_count * ACCOUNTS_PER_HOP != remaining_accounts.len()
+    {
+        return Err(FluxDexError::InvalidSwapPath.into());
+    }
+
+    let mut hops: Vec<RouteHop<'info>> = Vec::with_capacity(hop_count);
+    let mut current_mint = mint_in;
+    for accounts in remaining_accounts.chunks(ACCOUNTS_PER_HOP) {
+        if accounts
+            .iter()
+            .enumerate()
+            .any(|(i, account)| i != 3 && i != 4 && !account.is_writable)
+        {
+            return Err(FluxDexError::InvalidAccount.into());
+        }
+
+        let pool = Account::<Pool>::try_from(&accounts[0])?;
+        pool.check_operational()?;
+
+        // 集中流动性池需要 TickArray，不参与路由
+        if pool.curve_type == CurveType::Concentrated {
+            return Err(FluxDexError::InvalidCurveType.into());
+        }
+
+        // 同一个池子在路由中出现两次会以旧状态覆盖写回
+        if hops.iter().any(|hop| hop.pool.key() == pool.key()) {
+            return Err(FluxDexError::InvalidSwapPath.into());
+        }
+
+        let a_to_b = if current_mint == pool.token_a_mint {
+            true
+        } else if current_mint == pool.token_b_mint {
+            false
+        } else {
+            return Err(FluxDexError::InvalidSwapPath.into());
+        };
+        let (expected_vault_in, expected_vault_out, expected_mint_out) = if a_to_b {
+            (pool.token_a_vault, pool.token_b_vault, pool.token_b_mint)
+        } else {
+            (pool.token_b_vault, pool.token_a_vault, pool.token_a_mint)
+        };
+
+        let vault_in = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        let vault_out = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+        let hop_mint_out = InterfaceAccount::<Mint>::try_from(&accounts[3])?;
+        let token_program_out = Interface::<TokenInterface>::try_from(&accounts[4])?;
+        let price_oracle = Account::<PriceObservations>::try_from(&accounts[5])?;
+        if vault_in.key() != expected_vault_in
+            || vault_out.key() != expected_vault_out
+            || hop_mint_out.key() != expected_mint_out
+            || *accounts[3].owner != token_program_out.key()
+            || price_oracle.key() != pool.price_oracle
+        {
+            return Err(FluxDexError::InvalidAccount.into());
+        }
+
+        current_mint = expected_mint_out;
+        hops.push(RouteHop {
+            pool,
+            vault_in,
+            vault_out,
+            mint_out: hop_mint_out,
+            token_program_out,
+            price_oracle,
+            a_to_b,
+        });
+    }
+
+    if current_mint != mint_out {
+        return Err(FluxDexError::InvalidSwapPath.into());
+    }
+    Ok(hops)
+}
+
+/// 记录一跳的兑换并写回池子与价格观测账户
+#[allow(clippy::too_many_arguments)]
+fn settle_hop(
+    hop: &mut RouteHop,
+    user: Pubkey,
+    hop_index: u8,
+    amount_in: u64,
+    amount_out: u64,
+    fee_bps: u16,
+    protocol_fee_bps: u16,
+    now: i64,
+) -> Result<()> {
+    let fee_amount = MathUtils::calculate_fee_amount(amount_in, fee_bps)?;
+    let pool = &mut hop.pool;
+
+    // 储备量变化前记录价格观测
+    hop.price_oracle.observe(pool, now)?;
+
+    let pre_swap_price = pool.get_current_price(now);
+    pool.record_swap(
+        hop.a_to_b,
+        amount_in,
+        amount_out,
+        fee_amount,
+        protocol_fee_bps,
+    )?;
+    update_dynamic_fee(pool, &hop.price_oracle, pre_swap_price, now)?;
+    pool.updated_at = now;
+
+    // 储备量不变式检查
+    hop.vault_in.reload()?;
+    hop.vault_out.reload()?;
+    let (vault_a_amount, vault_b_amount) = if hop.a_to_b {
+        (hop.vault_in.amount, hop.vault_out.amount)
+    } else {
+        (hop.vault_out.amount, hop.vault_in.amount)
+    };
+    hop.pool
+        .check_reserves_backed(vault_a_amount, vault_b_amount)?;
+
+    hop.pool.exit(&crate::ID)?;
+    hop.price_oracle.exit(&crate::ID)?;
+
+    emit!(RouteHopExecuted {
+        user,
+        pool: hop.pool.key(),
+        hop_index,
+        a_to_b: hop.a_to_b,
+        amount_in,
+        amount_out,
+        fee_amount,
+        fee_bps,
+        timestamp: now,
+    });
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::GlobalFlag;
+use crate::FluxDexError;
+use crate::GlobalFlagUpdated;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        has_one = super_admin @ FluxDexError::Unauthorized,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// 切换 ALM、MEV 保护、跨链或全局紧急模式开关
+pub fn set_global_flag_handler(
+    ctx: Context<UpdateGlobalConfig>,
+    flag: GlobalFlag, // 要切换的开关
+    enabled: bool,    // 新的取值
+) -> Result<()> {
+    let config = &mut ctx.accounts.global_config;
+
+    // 紧急模式下只能切换紧急模式本身，以便恢复运行
+    if flag != GlobalFlag::EmergencyMode {
+        config.check_operational()?;
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_value = config.flag(flag);
+    config.set_flag(flag, enabled);
+
+    emit!(GlobalFlagUpdated {
+        super_admin: ctx.accounts.super_admin.key(),
+        flag,
+        old_value,
+        new_value: enabled,
+        timestamp: now,
+    });
+
+    msg!("✅ Global flag {:?}: {} -> {}", flag, old_value, enabled);
+    Ok(())
+}
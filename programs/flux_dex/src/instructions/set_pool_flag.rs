@@ -0,0 +1,69 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PoolFlag;
+use crate::FluxDexError;
+use crate::PoolFlagUpdated;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump,
+        has_one = authority @ FluxDexError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// 切换暂停、紧急模式、动态费率、ALM 或 MEV 保护开关
+pub fn set_pool_flag_handler(
+    ctx: Context<UpdatePool>,
+    flag: PoolFlag, // 要切换的开关
+    enabled: bool,  // 新的取值
+) -> Result<()> {
+    // 紧急模式下只能切换紧急模式本身，以便恢复运行
+    let global_config = &ctx.accounts.global_config;
+    if flag != PoolFlag::EmergencyMode {
+        global_config.check_operational()?;
+        ctx.accounts.pool.check_not_emergency()?;
+    }
+
+    // ALM 与 MEV 保护须在全局开关允许时才能启用
+    let globally_allowed = match flag {
+        PoolFlag::Alm => global_config.alm_enabled_globally,
+        PoolFlag::MevProtection => global_config.mev_protection_enabled_globally,
+        _ => true,
+    };
+    if enabled && !globally_allowed {
+        return Err(FluxDexError::FeatureDisabledGlobally.into());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_value = pool.flag(flag, global_config);
+    pool.set_flag(flag, enabled);
+    pool.updated_at = now;
+
+    emit!(PoolFlagUpdated {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        flag,
+        old_value,
+        new_value: enabled,
+        timestamp: now,
+    });
+
+    msg!("✅ Pool flag {:?}: {} -> {}", flag, old_value, enabled);
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::transfer_tokens_from_pool;
+use crate::FluxDexError;
+use crate::ReservesSkimmed;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // 超额部分的接收账户
+    #[account(
+        mut,
+        constraint = recipient_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub recipient_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+    )]
+    pub recipient_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+/// 将金库中超出储备量与协议费的部分转给指定账户，任何人都可调用
+pub fn skim_handler(ctx: Context<Skim>) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+    ctx.accounts.pool.check_unlocked()?;
+    let (owed_a, owed_b) = ctx.accounts.pool.owed_balances()?;
+
+    let surplus_a = ctx
+        .accounts
+        .pool_token_a_vault
+        .amount
+        .checked_sub(owed_a)
+        .ok_or(FluxDexError::ReserveExceedsVaultBalance)?;
+
+    let surplus_b = ctx
+        .accounts
+        .pool_token_b_vault
+        .amount
+        .checked_sub(owed_b)
+        .ok_or(FluxDexError::ReserveExceedsVaultBalance)?;
+
+    if surplus_a > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.recipient_token_a,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.token_a_program,
+            surplus_a,
+        )?;
+    }
+
+    if surplus_b > 0 {
+        transfer_tokens_from_pool(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.recipient_token_b,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.token_b_program,
+            surplus_b,
+        )?;
+    }
+
+    // 储备量不变式检查
+    ctx.accounts.pool_token_a_vault.reload()?;
+    ctx.accounts.pool_token_b_vault.reload()?;
+    ctx.accounts.pool.check_reserves_backed(
+        ctx.accounts.pool_token_a_vault.amount,
+        ctx.accounts.pool_token_b_vault.amount,
+    )?;
+
+    let clock = Clock::get()?;
+    emit!(ReservesSkimmed {
+        pool: ctx.accounts.pool.key(),
+        recipient_token_a: ctx.accounts.recipient_token_a.key(),
+        recipient_token_b: ctx.accounts.recipient_token_b.key(),
+        amount_a: surplus_a,
+        amount_b: surplus_b,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Surplus skimmed: {} token A, {} token B",
+        surplus_a,
+        surplus_b
+    );
+    Ok(())
+}
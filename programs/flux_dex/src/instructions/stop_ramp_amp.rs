@@ -0,0 +1,35 @@
+use crate::instructions::ramp_amp::RampAmp;
+use crate::state::CurveType;
+use crate::AmpRampStopped;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+pub fn stop_ramp_amp_handler(ctx: Context<RampAmp>) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    let pool = &mut ctx.accounts.pool;
+
+    if pool.curve_type != CurveType::StableSwap {
+        return Err(FluxDexError::InvalidCurveType.into());
+    }
+
+    // 将放大系数固定在当前值
+    let now = Clock::get()?.unix_timestamp;
+    let current_amp = pool.current_amp(now);
+
+    pool.amp_initial = current_amp;
+    pool.amp_target = current_amp;
+    pool.amp_ramp_start_ts = now;
+    pool.amp_ramp_stop_ts = now;
+    pool.updated_at = now;
+
+    emit!(AmpRampStopped {
+        pool: pool.key(),
+        amp: current_amp,
+        timestamp: now,
+    });
+
+    msg!("✅ Amp ramp stopped at {}", current_amp);
+    Ok(())
+}
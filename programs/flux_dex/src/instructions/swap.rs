@@ -0,0 +1,351 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::state::{ConcentratedSwapResult, CurveType, TickArray};
+use crate::FeeChanged;
+use crate::FixedPoint;
+use crate::FluxDexError;
+use crate::MathUtils;
+use crate::SwapExecuted;
+use crate::DYNAMIC_FEE_TWAP_WINDOW_SECS;
+use crate::{transfer_fee_amount, transfer_tokens_from_pool, transfer_tokens_to_pool};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_a.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_a: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ FluxDexError::InvalidAccount,
+        constraint = user_token_b.owner == user.key() @ FluxDexError::Unauthorized,
+    )]
+    pub user_token_b: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+
+    #[account(
+        address = pool.token_a_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_a_program,
+    )]
+    pub token_a_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = pool.token_b_mint @ FluxDexError::InvalidAccount,
+        mint::token_program = token_b_program,
+    )]
+    pub token_b_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token A / Token B 各自所属的代币程序（SPL Token 或 Token-2022）
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+pub fn swap_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    amount_in: u64,      // 输入代币数量
+    min_amount_out: u64, // 最少获得的输出数量（滑点保护）
+    a_to_b: bool,        // true: A -> B, false: B -> A
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    if amount_in == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    // 先转入再报价：按金库实际到账数量计算输出，兼容转账手续费
+    let amount_received = transfer_swap_input(ctx.accounts, a_to_b, amount_in)?;
+
+    let pool = &ctx.accounts.pool;
+    let fee_bps = pool.current_fee_bps;
+    let now = Clock::get()?.unix_timestamp;
+    let concentrated = if pool.curve_type == CurveType::Concentrated {
+        Some(execute_concentrated_swap(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            amount_received,
+            true,
+            a_to_b,
+            fee_bps,
+        )?)
+    } else {
+        None
+    };
+    let amount_out = match concentrated {
+        Some(result) => result.amount_out,
+        None => pool.calculate_swap_output(amount_received, a_to_b, fee_bps, now)?,
+    };
+
+    // 滑点保护按扣除转账手续费后用户实际到账的数量
+    let mint_out = if a_to_b {
+        &ctx.accounts.token_b_mint
+    } else {
+        &ctx.accounts.token_a_mint
+    };
+    let net_amount_out = amount_out.saturating_sub(transfer_fee_amount(mint_out, amount_out)?);
+    if amount_out == 0 || net_amount_out < min_amount_out {
+        return Err(FluxDexError::MinimumOutputNotMet.into());
+    }
+
+    settle_swap(
+        ctx.accounts,
+        a_to_b,
+        amount_received,
+        amount_out,
+        fee_bps,
+        concentrated,
+    )
+}
+
+/// 用户 -> 池子转入输入代币，返回金库实际到账的数量
+pub(crate) fn transfer_swap_input(accounts: &mut Swap, a_to_b: bool, amount: u64) -> Result<u64> {
+    let (user_in, vault_in, mint_in, token_program_in) = if a_to_b {
+        (
+            &accounts.user_token_a,
+            &mut accounts.pool_token_a_vault,
+            &accounts.token_a_mint,
+            &accounts.token_a_program,
+        )
+    } else {
+        (
+            &accounts.user_token_b,
+            &mut accounts.pool_token_b_vault,
+            &accounts.token_b_mint,
+            &accounts.token_b_program,
+        )
+    };
+
+    // check user Balance
+    if user_in.amount < amount {
+        return Err(FluxDexError::InsufficientLiquidity.into());
+    }
+
+    transfer_tokens_to_pool(
+        &accounts.user,
+        user_in,
+        vault_in,
+        mint_in,
+        token_program_in,
+        amount,
+    )
+}
+
+/// 集中流动性兑换：沿 remaining_accounts 中的 TickArray 计算兑换并写回穿过的 tick
+///
+/// TickArray 须按兑换方向依次传入，第一个包含当前 tick
+pub(crate) fn execute_concentrated_swap<'info>(
+    accounts: &Swap<'info>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    amount: u64,
+    exact_in: bool,
+    a_to_b: bool,
+    fee_bps: u16,
+) -> Result<ConcentratedSwapResult> {
+    let pool = &accounts.pool;
+    let span = TickArray::span(pool.tick_spacing);
+
+    let mut tick_arrays: Vec<Account<'info, TickArray>> =
+        Vec::with_capacity(remaining_accounts.len());
+    for account_info in remaining_accounts {
+        let tick_array = Account::<TickArray>::try_from(account_info)?;
+        let expected_start = match tick_arrays.last() {
+            None => TickArray::start_index_for(pool.current_tick, pool.tick_spacing),
+            Some(prev) if a_to_b => prev.start_tick_index - span,
+            Some(prev) => prev.start_tick_index + span,
+        };
+        if !account_info.is_writable
+            || tick_array.pool != pool.key()
+            || tick_array.start_tick_index != expected_start
+        {
+            return Err(FluxDexError::InvalidAccount.into());
+        }
+        tick_arrays.push(tick_array);
+    }
+
+    let result = pool.compute_concentrated_swap(
+        &mut tick_arrays,
+        amount,
+        exact_in,
+        a_to_b,
+        fee_bps,
+        accounts.global_config.protocol_fee_bps,
+    )?;
+
+    for tick_array in &tick_arrays {
+        tick_array.exit(&crate::ID)?;
+    }
+    Ok(result)
+}
+
+/// 转出兑换结果并更新状态、发出事件，输入代币须已由 transfer_swap_input 转入
+///
+/// amount_in 为金库实际到账的输入数量；concentrated 为集中流动性池预先计算好的兑换结果
+pub(crate) fn settle_swap(
+    accounts: &mut Swap,
+    a_to_b: bool,
+    amount_in: u64,
+    amount_out: u64,
+    fee_bps: u16,
+    concentrated: Option<ConcentratedSwapResult>,
+) -> Result<()> {
+    let (user_out, vault_out, mint_out, token_program_out) = if a_to_b {
+        (
+            &accounts.user_token_b,
+            &accounts.pool_token_b_vault,
+            &accounts.token_b_mint,
+            &accounts.token_b_program,
+        )
+    } else {
+        (
+            &accounts.user_token_a,
+            &accounts.pool_token_a_vault,
+            &accounts.token_a_mint,
+            &accounts.token_a_program,
+        )
+    };
+
+    // 池子 -> 用户
+    transfer_tokens_from_pool(
+        &accounts.pool,
+        vault_out,
+        user_out,
+        mint_out,
+        token_program_out,
+        amount_out,
+    )?;
+
+    // 更新池子状态
+    let fee_amount = match concentrated {
+        Some(result) => result.fee_amount,
+        None => MathUtils::calculate_fee_amount(amount_in, fee_bps)?,
+    };
+    let protocol_fee_bps = accounts.global_config.protocol_fee_bps;
+    let pool = &mut accounts.pool;
+
+    // 储备量变化前记录价格观测
+    let clock = Clock::get()?;
+    accounts.price_oracle.observe(pool, clock.unix_timestamp)?;
+
+    let pre_swap_price = pool.get_current_price(clock.unix_timestamp);
+    match concentrated {
+        Some(result) => pool.record_concentrated_swap(a_to_b, &result)?,
+        None => pool.record_swap(a_to_b, amount_in, amount_out, fee_amount, protocol_fee_bps)?,
+    }
+    update_dynamic_fee(
+        pool,
+        &accounts.price_oracle,
+        pre_swap_price,
+        clock.unix_timestamp,
+    )?;
+
+    pool.updated_at = clock.unix_timestamp;
+
+    // 储备量不变式检查
+    accounts.pool_token_a_vault.reload()?;
+    accounts.pool_token_b_vault.reload()?;
+    accounts.pool.check_reserves_backed(
+        accounts.pool_token_a_vault.amount,
+        accounts.pool_token_b_vault.amount,
+    )?;
+
+    emit!(SwapExecuted {
+        user: accounts.user.key(),
+        pool: accounts.pool.key(),
+        a_to_b,
+        amount_in,
+        amount_out,
+        fee_amount,
+        fee_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Swap executed: {} in, {} out, {} fee",
+        amount_in,
+        amount_out,
+        fee_amount
+    );
+    Ok(())
+}
+
+/// 兑换后更新动态费率（仅在 dynamic_fee_enabled 时生效）
+///
+/// 以 TWAP 作为波动率基准；观测历史不足时退回到兑换前的现价
+pub(crate) fn update_dynamic_fee(
+    pool: &mut Account<Pool>,
+    price_oracle: &PriceObservations,
+    pre_swap_price: FixedPoint,
+    now: i64,
+) -> Result<()> {
+    if !pool.dynamic_fee_enabled {
+        return Ok(());
+    }
+
+    let reference_price = match price_oracle.consult(pool, now, DYNAMIC_FEE_TWAP_WINDOW_SECS) {
+        Ok(twap) => twap.price_a,
+        Err(_) => pre_swap_price,
+    };
+
+    let old_fee_bps = pool.current_fee_bps;
+    let liquidity_score = pool.refresh_dynamic_fee(reference_price, now)?;
+
+    if pool.current_fee_bps != old_fee_bps {
+        emit!(FeeChanged {
+            pool: pool.key(),
+            old_fee_bps,
+            new_fee_bps: pool.current_fee_bps,
+            volatility_score: pool.volatility_score,
+            liquidity_score,
+            timestamp: now,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::amount_with_transfer_fee;
+use crate::instructions::swap::{
+    execute_concentrated_swap, settle_swap, transfer_swap_input, Swap,
+};
+use crate::state::CurveType;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+pub fn swap_exact_out_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+    amount_out: u64,    // 池子转出的输出代币数量（转账手续费由接收方承担）
+    max_amount_in: u64, // 最多愿意支付的输入数量（滑点保护）
+    a_to_b: bool,       // true: A -> B, false: B -> A
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    ctx.accounts.global_config.check_operational()?;
+
+    // 池子处于紧急模式、已暂停或正在闪电贷回调中时拒绝
+    pool.check_operational()?;
+
+    if amount_out == 0 {
+        return Err(FluxDexError::InvalidInputAmount.into());
+    }
+
+    let fee_bps = pool.current_fee_bps;
+    let now = Clock::get()?.unix_timestamp;
+    let concentrated = if pool.curve_type == CurveType::Concentrated {
+        Some(execute_concentrated_swap(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            amount_out,
+            false,
+            a_to_b,
+            fee_bps,
+        )?)
+    } else {
+        None
+    };
+    let amount_in = match concentrated {
+        Some(result) => result.amount_in,
+        None => pool.calculate_swap_input(amount_out, a_to_b, fee_bps, now)?,
+    };
+
+    // 带转账手续费的 mint 需要多转入手续费部分，保证金库足额到账
+    let mint_in = if a_to_b {
+        &ctx.accounts.token_a_mint
+    } else {
+        &ctx.accounts.token_b_mint
+    };
+    let gross_amount_in = amount_with_transfer_fee(mint_in, amount_in)?;
+    if gross_amount_in > max_amount_in {
+        return Err(FluxDexError::SlippageExceeded.into());
+    }
+
+    let amount_received = transfer_swap_input(ctx.accounts, a_to_b, gross_amount_in)?;
+    if amount_received < amount_in {
+        return Err(FluxDexError::InvalidCalculation.into());
+    }
+
+    settle_swap(
+        ctx.accounts,
+        a_to_b,
+        amount_in,
+        amount_out,
+        fee_bps,
+        concentrated,
+    )
+}
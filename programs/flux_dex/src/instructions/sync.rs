@@ -0,0 +1,89 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::state::PriceObservations;
+use crate::FluxDexError;
+use crate::ReservesSynced;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        constraint = pool_token_a_vault.key() == pool.token_a_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_a_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = pool_token_b_vault.key() == pool.token_b_vault @ FluxDexError::InvalidAccount,
+    )]
+    pub pool_token_b_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// 价格观测账户
+    #[account(
+        mut,
+        seeds = [OBSERVATION_SEED, pool.key().as_ref()],
+        bump = price_oracle.bump,
+    )]
+    pub price_oracle: Account<'info, PriceObservations>,
+}
+
+/// 将储备量同步为金库实际余额（扣除待提取的协议费与 LP 手续费），任何人都可调用
+pub fn sync_handler(ctx: Context<SyncReserves>) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+    ctx.accounts.pool.check_unlocked()?;
+    let vault_a_amount = ctx.accounts.pool_token_a_vault.amount;
+    let vault_b_amount = ctx.accounts.pool_token_b_vault.amount;
+
+    let pool = &mut ctx.accounts.pool;
+    let old_reserve_a = pool.token_a_reserve;
+    let old_reserve_b = pool.token_b_reserve;
+
+    // 储备量变化前记录价格观测
+    let clock = Clock::get()?;
+    ctx.accounts
+        .price_oracle
+        .observe(pool, clock.unix_timestamp)?;
+
+    pool.token_a_reserve = vault_a_amount
+        .checked_sub(pool.protocol_fees_a)
+        .and_then(|amount| amount.checked_sub(pool.unclaimed_lp_fees_a))
+        .ok_or(FluxDexError::ReserveExceedsVaultBalance)?;
+
+    pool.token_b_reserve = vault_b_amount
+        .checked_sub(pool.protocol_fees_b)
+        .and_then(|amount| amount.checked_sub(pool.unclaimed_lp_fees_b))
+        .ok_or(FluxDexError::ReserveExceedsVaultBalance)?;
+
+    pool.updated_at = clock.unix_timestamp;
+
+    emit!(ReservesSynced {
+        pool: pool.key(),
+        old_reserve_a,
+        old_reserve_b,
+        new_reserve_a: pool.token_a_reserve,
+        new_reserve_b: pool.token_b_reserve,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "✅ Reserves synced: {} token A, {} token B",
+        pool.token_a_reserve,
+        pool.token_b_reserve
+    );
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::instructions::set_pool_flag::UpdatePool;
+use crate::state::MAX_FEE_BPS;
+use crate::BaseFeeUpdated;
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+/// 更新基础费率；fee_tier 是 Pool PDA 种子的一部分，保持不变
+pub fn update_base_fee_handler(
+    ctx: Context<UpdatePool>,
+    new_base_fee_bps: u16, // 新的基础费率
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    if new_base_fee_bps > MAX_FEE_BPS {
+        return Err(FluxDexError::InvalidFeeTier.into());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_base_fee_bps = pool.base_fee_bps;
+    pool.base_fee_bps = new_base_fee_bps;
+
+    // 未启用动态费率时立即生效，否则在下一笔兑换时按新的基础费率重新计算
+    if !pool.dynamic_fee_enabled {
+        pool.current_fee_bps = new_base_fee_bps;
+    }
+    pool.updated_at = now;
+
+    emit!(BaseFeeUpdated {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        old_base_fee_bps,
+        new_base_fee_bps,
+        timestamp: now,
+    });
+
+    msg!(
+        "✅ Base fee updated: {} -> {} bps",
+        old_base_fee_bps,
+        new_base_fee_bps
+    );
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::state::seeds::*;
+use crate::state::GlobalConfig;
+use crate::state::Pool;
+use crate::utils::trim_padding;
+use crate::FluxDexError;
+use crate::LpMetadataUriUpdated;
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{
+    update_metadata_accounts_v2, Metadata, MetadataAccount, UpdateMetadataAccountsV2,
+};
+
+#[derive(Accounts)]
+pub struct UpdateLpMetadataUri<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_SEED, pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &pool.fee_tier.to_le_bytes(), &[pool.curve_type as u8]],
+        bump = pool.bump,
+        has_one = authority @ FluxDexError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// LP 代币的 Metaplex 元数据账户
+    #[account(
+        mut,
+        seeds = [METADATA_SEED, token_metadata_program.key().as_ref(), pool.lp_mint.as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump,
+    )]
+    pub lp_metadata: Account<'info, MetadataAccount>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+pub fn update_lp_metadata_uri_handler(
+    ctx: Context<UpdateLpMetadataUri>,
+    uri: String, // 新的元数据 URI
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+    ctx.accounts.pool.check_not_emergency()?;
+
+    if uri.len() > mpl_token_metadata::MAX_URI_LENGTH {
+        return Err(FluxDexError::InvalidMetadata.into());
+    }
+
+    let pool = &ctx.accounts.pool;
+    let metadata = &ctx.accounts.lp_metadata;
+    let old_uri = trim_padding(&metadata.uri).to_string();
+
+    // 名称、符号等其余字段保持不变
+    let data = DataV2 {
+        name: trim_padding(&metadata.name).to_string(),
+        symbol: trim_padding(&metadata.symbol).to_string(),
+        uri: uri.clone(),
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: metadata.creators.clone(),
+        collection: metadata.collection.clone(),
+        uses: metadata.uses.clone(),
+    };
+
+    let fee_tier = pool.fee_tier.to_le_bytes();
+    let curve_type = [pool.curve_type as u8];
+    let authority_seed = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &fee_tier[..],
+        &curve_type[..],
+        &[pool.bump],
+    ];
+    let signer = &[&authority_seed[..]];
+
+    let cpi_accounts = UpdateMetadataAccountsV2 {
+        metadata: ctx.accounts.lp_metadata.to_account_info(),
+        update_authority: pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_metadata_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+    update_metadata_accounts_v2(cpi_ctx, None, Some(data), None, None)?;
+
+    let clock = Clock::get()?;
+    emit!(LpMetadataUriUpdated {
+        pool: pool.key(),
+        lp_mint: pool.lp_mint,
+        old_uri,
+        new_uri: uri.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("✅ LP metadata URI updated: {}", uri);
+    Ok(())
+}
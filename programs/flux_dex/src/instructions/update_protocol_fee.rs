@@ -0,0 +1,37 @@
+use crate::instructions::set_global_flag::UpdateGlobalConfig;
+use crate::FluxDexError;
+use crate::ProtocolFeeUpdated;
+use anchor_lang::prelude::*;
+
+/// 更新协议从手续费中抽取的比例，对之后的兑换生效
+pub fn update_protocol_fee_handler(
+    ctx: Context<UpdateGlobalConfig>,
+    new_protocol_fee_bps: u16, // 新的协议费比例
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+
+    // 协议费是手续费的一部分，不能超过 100%
+    if new_protocol_fee_bps > 10000 {
+        return Err(FluxDexError::InvalidFeeTier.into());
+    }
+
+    let config = &mut ctx.accounts.global_config;
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_protocol_fee_bps = config.protocol_fee_bps;
+    config.protocol_fee_bps = new_protocol_fee_bps;
+
+    emit!(ProtocolFeeUpdated {
+        super_admin: ctx.accounts.super_admin.key(),
+        old_protocol_fee_bps,
+        new_protocol_fee_bps,
+        timestamp: now,
+    });
+
+    msg!(
+        "✅ Protocol fee updated: {} -> {} bps",
+        old_protocol_fee_bps,
+        new_protocol_fee_bps
+    );
+    Ok(())
+}
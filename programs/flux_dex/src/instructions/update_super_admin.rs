@@ -0,0 +1,35 @@
+use crate::instructions::set_global_flag::UpdateGlobalConfig;
+use crate::FluxDexError;
+use crate::SuperAdminUpdated;
+use anchor_lang::prelude::*;
+
+/// 移交超级管理员，移交后原管理员立即失去全局配置权限
+pub fn update_super_admin_handler(
+    ctx: Context<UpdateGlobalConfig>,
+    new_super_admin: Pubkey, // 新的超级管理员
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+
+    if new_super_admin == Pubkey::default() {
+        return Err(FluxDexError::InvalidAccount.into());
+    }
+
+    let config = &mut ctx.accounts.global_config;
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_super_admin = config.super_admin;
+    config.super_admin = new_super_admin;
+
+    emit!(SuperAdminUpdated {
+        old_super_admin,
+        new_super_admin,
+        timestamp: now,
+    });
+
+    msg!(
+        "✅ Super admin updated: {} -> {}",
+        old_super_admin,
+        new_super_admin
+    );
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::instructions::set_global_flag::UpdateGlobalConfig;
+use crate::FluxDexError;
+use crate::TreasuryUpdated;
+use anchor_lang::prelude::*;
+
+/// 更新协议费接收方，已累计未提取的协议费之后转入新国库
+pub fn update_treasury_handler(
+    ctx: Context<UpdateGlobalConfig>,
+    new_treasury: Pubkey, // 新的协议费接收方
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+
+    if new_treasury == Pubkey::default() {
+        return Err(FluxDexError::InvalidAccount.into());
+    }
+
+    let config = &mut ctx.accounts.global_config;
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_treasury = config.treasury;
+    config.treasury = new_treasury;
+
+    emit!(TreasuryUpdated {
+        super_admin: ctx.accounts.super_admin.key(),
+        old_treasury,
+        new_treasury,
+        timestamp: now,
+    });
+
+    msg!("✅ Treasury updated: {} -> {}", old_treasury, new_treasury);
+    Ok(())
+}
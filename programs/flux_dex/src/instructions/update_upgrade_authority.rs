@@ -0,0 +1,36 @@
+use crate::instructions::set_global_flag::UpdateGlobalConfig;
+use crate::FluxDexError;
+use crate::UpgradeAuthorityUpdated;
+use anchor_lang::prelude::*;
+
+/// 更新全局配置中记录的升级权限
+pub fn update_upgrade_authority_handler(
+    ctx: Context<UpdateGlobalConfig>,
+    new_upgrade_authority: Pubkey, // 新的升级权限
+) -> Result<()> {
+    ctx.accounts.global_config.check_operational()?;
+
+    if new_upgrade_authority == Pubkey::default() {
+        return Err(FluxDexError::InvalidAccount.into());
+    }
+
+    let config = &mut ctx.accounts.global_config;
+    let now = Clock::get()?.unix_timestamp;
+
+    let old_upgrade_authority = config.upgrade_authority;
+    config.upgrade_authority = new_upgrade_authority;
+
+    emit!(UpgradeAuthorityUpdated {
+        super_admin: ctx.accounts.super_admin.key(),
+        old_upgrade_authority,
+        new_upgrade_authority,
+        timestamp: now,
+    });
+
+    msg!(
+        "✅ Upgrade authority updated: {} -> {}",
+        old_upgrade_authority,
+        new_upgrade_authority
+    );
+    Ok(())
+}
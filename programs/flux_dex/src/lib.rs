@@ -14,29 +14,277 @@ pub use utils::*;
 
 declare_id!("EKbi7QjTXoTk5hSpRh5fpscNDrGut2yUaohtfemH1Peg");
 
-#[program]
-pub mod flux_dex {
+// anchor 0.31 的 #[program] 宏在模块外生成的 IDL 指令调用了已弃用的 AccountInfo::realloc，
+// 用一层模块把 allow 限定在宏展开内
+#[allow(deprecated)]
+mod program_entry {
     use super::*;
 
-    pub fn initialize_pool(ctx: Context<InitializePool>, base_fee_bps: u16) -> Result<()> {
-        instructions::initialize_pool_handler(ctx, base_fee_bps)
-    }
+    #[program]
+    pub mod flux_dex {
+        use super::*;
+
+        pub fn initialize_pool(
+            ctx: Context<InitializePool>,
+            base_fee_bps: u16,
+            curve_type: CurveType,
+            amp: u64,
+            weight_a_bps: u16,
+            tick_spacing: u16,
+            initial_sqrt_price_x64: u128,
+        ) -> Result<()> {
+            instructions::initialize_pool_handler(
+                ctx,
+                base_fee_bps,
+                curve_type,
+                amp,
+                weight_a_bps,
+                tick_spacing,
+                initial_sqrt_price_x64,
+            )
+        }
+
+        pub fn add_liquidity(
+            ctx: Context<AddLiquidity>,
+            desired_amount_a: u64,
+            desired_amount_b: u64,
+            min_amount_a: u64,
+            min_amount_b: u64,
+            min_lp_tokens: u64,
+        ) -> Result<()> {
+            instructions::add_liquidity_handler(
+                ctx,
+                desired_amount_a,
+                desired_amount_b,
+                min_amount_a,
+                min_amount_b,
+                min_lp_tokens,
+            )
+        }
+
+        pub fn swap<'info>(
+            ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+            amount_in: u64,
+            min_amount_out: u64,
+            a_to_b: bool,
+        ) -> Result<()> {
+            instructions::swap_handler(ctx, amount_in, min_amount_out, a_to_b)
+        }
+
+        pub fn swap_exact_out<'info>(
+            ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+            amount_out: u64,
+            max_amount_in: u64,
+            a_to_b: bool,
+        ) -> Result<()> {
+            instructions::swap_exact_out_handler(ctx, amount_out, max_amount_in, a_to_b)
+        }
+
+        pub fn remove_liquidity(
+            ctx: Context<RemoveLiquidity>,
+            lp_amount: u64,
+            min_amount_a: u64,
+            min_amount_b: u64,
+        ) -> Result<()> {
+            instructions::remove_liquidity_handler(ctx, lp_amount, min_amount_a, min_amount_b)
+        }
+
+        pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+            instructions::close_position_handler(ctx)
+        }
+
+        pub fn add_liquidity_single_sided(
+            ctx: Context<AddLiquiditySingleSided>,
+            amount_in: u64,
+            min_lp_tokens: u64,
+        ) -> Result<()> {
+            instructions::add_liquidity_single_sided_handler(ctx, amount_in, min_lp_tokens)
+        }
+
+        pub fn remove_liquidity_single_sided(
+            ctx: Context<RemoveLiquiditySingleSided>,
+            lp_amount: u64,
+            min_amount_out: u64,
+        ) -> Result<()> {
+            instructions::remove_liquidity_single_sided_handler(ctx, lp_amount, min_amount_out)
+        }
+
+        pub fn initialize_global_config(
+            ctx: Context<InitializeGlobalConfig>,
+            protocol_fee_bps: u16,
+            treasury: Pubkey,
+        ) -> Result<()> {
+            instructions::initialize_global_config_handler(ctx, protocol_fee_bps, treasury)
+        }
+
+        pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+            instructions::collect_protocol_fees_handler(ctx)
+        }
+
+        pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+            instructions::sync_handler(ctx)
+        }
+
+        pub fn skim(ctx: Context<Skim>) -> Result<()> {
+            instructions::skim_handler(ctx)
+        }
+
+        pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+            instructions::collect_fees_handler(ctx)
+        }
+
+        pub fn consult(ctx: Context<Consult>, window_secs: u32) -> Result<TwapPrice> {
+            instructions::consult_handler(ctx, window_secs)
+        }
+
+        pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_stop_ts: i64) -> Result<()> {
+            instructions::ramp_amp_handler(ctx, target_amp, ramp_stop_ts)
+        }
+
+        pub fn stop_ramp_amp(ctx: Context<RampAmp>) -> Result<()> {
+            instructions::stop_ramp_amp_handler(ctx)
+        }
+
+        pub fn initialize_tick_array(
+            ctx: Context<InitializeTickArray>,
+            start_tick_index: i32,
+        ) -> Result<()> {
+            instructions::initialize_tick_array_handler(ctx, start_tick_index)
+        }
+
+        pub fn add_concentrated_liquidity(
+            ctx: Context<AddConcentratedLiquidity>,
+            tick_lower: i32,
+            tick_upper: i32,
+            liquidity: u128,
+            max_amount_a: u64,
+            max_amount_b: u64,
+        ) -> Result<()> {
+            instructions::add_concentrated_liquidity_handler(
+                ctx,
+                tick_lower,
+                tick_upper,
+                liquidity,
+                max_amount_a,
+                max_amount_b,
+            )
+        }
+
+        pub fn remove_concentrated_liquidity(
+            ctx: Context<RemoveConcentratedLiquidity>,
+            liquidity: u128,
+            min_amount_a: u64,
+            min_amount_b: u64,
+        ) -> Result<()> {
+            instructions::remove_concentrated_liquidity_handler(
+                ctx,
+                liquidity,
+                min_amount_a,
+                min_amount_b,
+            )
+        }
+
+        pub fn update_lp_metadata_uri(
+            ctx: Context<UpdateLpMetadataUri>,
+            uri: String,
+        ) -> Result<()> {
+            instructions::update_lp_metadata_uri_handler(ctx, uri)
+        }
+
+        pub fn flash_loan<'info>(
+            ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+            amount_a: u64,
+            amount_b: u64,
+            data: Vec<u8>,
+        ) -> Result<()> {
+            instructions::flash_loan_handler(ctx, amount_a, amount_b, data)
+        }
+
+        pub fn route_swap<'info>(
+            ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+            amount_in: u64,
+            min_amount_out: u64,
+        ) -> Result<()> {
+            instructions::route_swap_handler(ctx, amount_in, min_amount_out)
+        }
+
+        pub fn set_pool_flag(
+            ctx: Context<UpdatePool>,
+            flag: PoolFlag,
+            enabled: bool,
+        ) -> Result<()> {
+            instructions::set_pool_flag_handler(ctx, flag, enabled)
+        }
+
+        pub fn update_base_fee(ctx: Context<UpdatePool>, new_base_fee_bps: u16) -> Result<()> {
+            instructions::update_base_fee_handler(ctx, new_base_fee_bps)
+        }
+
+        pub fn propose_authority(
+            ctx: Context<TransferPoolAuthority>,
+            role: AuthorityRole,
+            new_holder: Pubkey,
+        ) -> Result<()> {
+            instructions::propose_authority_handler(ctx, role, new_holder)
+        }
+
+        pub fn accept_authority(
+            ctx: Context<TransferPoolAuthority>,
+            role: AuthorityRole,
+        ) -> Result<()> {
+            instructions::accept_authority_handler(ctx, role)
+        }
+
+        pub fn cancel_authority_transfer(
+            ctx: Context<TransferPoolAuthority>,
+            role: AuthorityRole,
+        ) -> Result<()> {
+            instructions::cancel_authority_transfer_handler(ctx, role)
+        }
+
+        pub fn set_global_flag(
+            ctx: Context<UpdateGlobalConfig>,
+            flag: GlobalFlag,
+            enabled: bool,
+        ) -> Result<()> {
+            instructions::set_global_flag_handler(ctx, flag, enabled)
+        }
+
+        pub fn update_protocol_fee(
+            ctx: Context<UpdateGlobalConfig>,
+            new_protocol_fee_bps: u16,
+        ) -> Result<()> {
+            instructions::update_protocol_fee_handler(ctx, new_protocol_fee_bps)
+        }
+
+        pub fn update_treasury(
+            ctx: Context<UpdateGlobalConfig>,
+            new_treasury: Pubkey,
+        ) -> Result<()> {
+            instructions::update_treasury_handler(ctx, new_treasury)
+        }
+
+        pub fn update_super_admin(
+            ctx: Context<UpdateGlobalConfig>,
+            new_super_admin: Pubkey,
+        ) -> Result<()> {
+            instructions::update_super_admin_handler(ctx, new_super_admin)
+        }
+
+        pub fn update_upgrade_authority(
+            ctx: Context<UpdateGlobalConfig>,
+            new_upgrade_authority: Pubkey,
+        ) -> Result<()> {
+            instructions::update_upgrade_authority_handler(ctx, new_upgrade_authority)
+        }
+
+        pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>, lp_amount: u64) -> Result<()> {
+            instructions::emergency_withdraw_handler(ctx, lp_amount)
+        }
 
-    pub fn add_liquidity(
-        ctx: Context<AddLiquidity>,
-        desired_amount_a: u64,
-        desired_amount_b: u64,
-        min_amount_a: u64,
-        min_amount_b: u64,
-        min_lp_tokens: u64,
-    ) -> Result<()> {
-        instructions::add_liquidity_handler(
-            ctx,
-            desired_amount_a,
-            desired_amount_b,
-            min_amount_a,
-            min_amount_b,
-            min_lp_tokens,
-        )
+        pub fn emergency_withdraw_range(ctx: Context<EmergencyWithdrawRange>) -> Result<()> {
+            instructions::emergency_withdraw_range_handler(ctx)
+        }
     }
 }
+pub use program_entry::*;
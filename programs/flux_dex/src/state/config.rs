@@ -1,3 +1,4 @@
+use crate::error::FluxDexError;
 use anchor_lang::prelude::*;
 
 /// 全局配置账户
@@ -39,6 +40,47 @@ impl GlobalConfig {
         8 + 16 + 8 + // statistics
         8 + 1 + // metadata
         32; // padding
+
+    /// 全局紧急模式下除 emergency_withdraw 外拒绝所有操作
+    pub fn check_operational(&self) -> Result<()> {
+        if self.emergency_mode {
+            return Err(FluxDexError::EmergencyModeActive.into());
+        }
+        Ok(())
+    }
+
+    /// 读取全局开关的当前值
+    pub fn flag(&self, flag: GlobalFlag) -> bool {
+        match flag {
+            GlobalFlag::Alm => self.alm_enabled_globally,
+            GlobalFlag::MevProtection => self.mev_protection_enabled_globally,
+            GlobalFlag::CrossChain => self.cross_chain_enabled,
+            GlobalFlag::EmergencyMode => self.emergency_mode,
+        }
+    }
+
+    /// 设置全局开关
+    pub fn set_flag(&mut self, flag: GlobalFlag, enabled: bool) {
+        match flag {
+            GlobalFlag::Alm => self.alm_enabled_globally = enabled,
+            GlobalFlag::MevProtection => self.mev_protection_enabled_globally = enabled,
+            GlobalFlag::CrossChain => self.cross_chain_enabled = enabled,
+            GlobalFlag::EmergencyMode => self.emergency_mode = enabled,
+        }
+    }
+}
+
+/// 超级管理员可切换的全局开关
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum GlobalFlag {
+    /// 允许池子启用智能流动性管理
+    Alm,
+    /// 允许池子启用 MEV 保护
+    MevProtection,
+    /// 跨链功能
+    CrossChain,
+    /// 全局紧急模式
+    EmergencyMode,
 }
 
 /// 用户配置
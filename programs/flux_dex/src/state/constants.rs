@@ -12,3 +12,57 @@ pub const MIN_LIQUIDITY_THRESHOLD: u64 = 1000;
 
 /// 默认重新平衡阈值 5%
 pub const DEFAULT_REBALANCE_THRESHOLD_BPS: u16 = 500;
+
+/// 每个池子保存的价格观测数量
+pub const MAX_OBSERVATIONS: usize = 64;
+
+/// 动态费率：波动率 EWMA 平滑系数 20%
+pub const VOLATILITY_EWMA_ALPHA_BPS: u16 = 2000;
+
+/// 动态费率：作为波动率基准的 TWAP 窗口(秒)
+pub const DYNAMIC_FEE_TWAP_WINDOW_SECS: u32 = 300;
+
+/// 动态费率：流动性分数满分对应的池子深度 sqrt(reserve_a * reserve_b)
+pub const LIQUIDITY_SCORE_FULL_DEPTH: u64 = 1_000_000_000_000;
+
+/// StableSwap 放大系数下限
+pub const MIN_AMP: u64 = 1;
+
+/// StableSwap 放大系数上限
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// 单次调整放大系数的最大倍数
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// 放大系数调整的最短持续时间(秒)
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+
+/// 加权池单个代币的最小权重 1%
+pub const MIN_WEIGHT_BPS: u16 = 100;
+
+/// 加权池单笔输入不超过输入储备的 30%
+pub const MAX_IN_RATIO_BPS: u16 = 3000;
+
+/// 加权池单笔输出不超过输出储备的 30%
+pub const MAX_OUT_RATIO_BPS: u16 = 3000;
+
+/// 集中流动性：最小 tick
+pub const MIN_TICK: i32 = -443_636;
+
+/// 集中流动性：最大 tick
+pub const MAX_TICK: i32 = 443_636;
+
+/// 集中流动性：最大 tick 间距
+pub const MAX_TICK_SPACING: u16 = 16_384;
+
+/// 集中流动性：每个 TickArray 账户包含的 tick 数量
+pub const TICK_ARRAY_SIZE: usize = 32;
+
+/// LP 代币元数据名称前缀
+pub const LP_TOKEN_NAME_PREFIX: &str = "FLUX-LP";
+
+/// LP 代币符号中每个代币符号的最大字节数
+pub const PAIR_SYMBOL_MAX_LEN: usize = 4;
+
+/// 路由兑换的最大跳数
+pub const MAX_ROUTE_HOPS: usize = 4;
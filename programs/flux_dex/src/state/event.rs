@@ -1,3 +1,4 @@
+use crate::state::{AuthorityRole, GlobalFlag, PoolFlag};
 use anchor_lang::prelude::*;
 
 #[event]
@@ -9,3 +10,267 @@ pub struct LiquidityAdded {
     pub lp_tokens: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct LiquidityRemoved {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_tokens: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub a_to_b: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionClosed {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReservesSynced {
+    pub pool: Pubkey,
+    pub old_reserve_a: u64,
+    pub old_reserve_b: u64,
+    pub new_reserve_a: u64,
+    pub new_reserve_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReservesSkimmed {
+    pub pool: Pubkey,
+    pub recipient_token_a: Pubkey,
+    pub recipient_token_b: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeChanged {
+    pub pool: Pubkey,
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub volatility_score: u16,
+    pub liquidity_score: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AmpRampStarted {
+    pub pool: Pubkey,
+    pub old_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_ts: i64,
+    pub ramp_stop_ts: i64,
+}
+
+#[event]
+pub struct AmpRampStopped {
+    pub pool: Pubkey,
+    pub amp: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RangeLiquidityAdded {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RangeLiquidityRemoved {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpMetadataUriUpdated {
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub old_uri: String,
+    pub new_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanExecuted {
+    pub borrower: Pubkey,
+    pub pool: Pubkey,
+    pub receiver_program: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteHopExecuted {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub hop_index: u8,
+    pub a_to_b: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolFlagUpdated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub flag: PoolFlag,
+    pub old_value: bool,
+    pub new_value: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BaseFeeUpdated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_base_fee_bps: u16,
+    pub new_base_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub pool: Pubkey,
+    pub role: AuthorityRole,
+    pub current: Pubkey,
+    pub pending: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub pool: Pubkey,
+    pub role: AuthorityRole,
+    pub old: Pubkey,
+    pub new: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub pool: Pubkey,
+    pub role: AuthorityRole,
+    pub current: Pubkey,
+    pub cancelled: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GlobalFlagUpdated {
+    pub super_admin: Pubkey,
+    pub flag: GlobalFlag,
+    pub old_value: bool,
+    pub new_value: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeeUpdated {
+    pub super_admin: Pubkey,
+    pub old_protocol_fee_bps: u16,
+    pub new_protocol_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub super_admin: Pubkey,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SuperAdminUpdated {
+    pub old_super_admin: Pubkey,
+    pub new_super_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UpgradeAuthorityUpdated {
+    pub super_admin: Pubkey,
+    pub old_upgrade_authority: Pubkey,
+    pub new_upgrade_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawn {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub lp_tokens: u64,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fees_a: u64,
+    pub fees_b: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RangeEmergencyWithdrawn {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fees_a: u64,
+    pub fees_b: u64,
+    pub timestamp: i64,
+}
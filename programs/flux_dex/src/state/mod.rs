@@ -1,17 +1,23 @@
 pub mod ai_config;
 pub mod bridge;
+pub mod config;
 pub mod constants;
 pub mod event;
 pub mod intent;
+pub mod oracle;
 pub mod pool;
 pub mod position;
 pub mod seeds;
+pub mod tick;
 
 pub use ai_config::*;
 pub use bridge::*;
+pub use config::*;
 pub use constants::*;
 pub use event::*;
 pub use intent::*;
+pub use oracle::*;
 pub use pool::*;
 pub use position::*;
 pub use seeds::*;
+pub use tick::*;
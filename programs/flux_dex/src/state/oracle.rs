@@ -0,0 +1,162 @@
+use crate::error::FluxDexError;
+use crate::state::Pool;
+use crate::state::MAX_OBSERVATIONS;
+use crate::utils::FixedPoint;
+use anchor_lang::prelude::*;
+
+/// 价格观测环形缓冲区（每个池子一个）
+#[account]
+pub struct PriceObservations {
+    /// 关联池子
+    pub pool: Pubkey,
+
+    /// 最新观测在缓冲区中的位置
+    pub index: u16,
+
+    /// 观测记录，写满 MAX_OBSERVATIONS 后循环覆盖
+    pub observations: Vec<Observation>,
+
+    pub bump: u8,
+}
+
+/// 单次价格观测
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_a_cumulative: u128,
+    pub price_b_cumulative: u128,
+}
+
+/// 时间加权平均价格
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TwapPrice {
+    pub price_a: FixedPoint, // 以 Token B 计价的 Token A 价格
+    pub price_b: FixedPoint, // 以 Token A 计价的 Token B 价格
+    pub window_secs: i64,    // 实际覆盖的时间窗口
+}
+
+impl PriceObservations {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // pool
+        2 + // index
+        4 + MAX_OBSERVATIONS * (8 + 16 + 16) + // observations
+        1 + // bump
+        16; // padding
+
+    /// 更新池子累计价格并写入一条观测，须在储备量变化之前调用
+    pub fn observe(&mut self, pool: &mut Pool, now: i64) -> Result<()> {
+        pool.update_price_accumulators(now)?;
+
+        if let Some(latest) = self.observations.get(self.index as usize) {
+            // 同一秒内只记录一次
+            if latest.timestamp >= now {
+                return Ok(());
+            }
+        }
+
+        let observation = Observation {
+            timestamp: now,
+            price_a_cumulative: pool.price_a_cumulative,
+            price_b_cumulative: pool.price_b_cumulative,
+        };
+
+        if self.observations.len() < MAX_OBSERVATIONS {
+            self.observations.push(observation);
+            self.index = (self.observations.len() - 1) as u16;
+        } else {
+            self.index = ((self.index as usize + 1) % MAX_OBSERVATIONS) as u16;
+            self.observations[self.index as usize] = observation;
+        }
+        Ok(())
+    }
+
+    /// 读取至少覆盖 window_secs 的 TWAP
+    ///
+    /// 取时间不晚于 now - window_secs 的最新观测作为起点，历史不足时返回错误
+    pub fn consult(&self, pool: &Pool, now: i64, window_secs: u32) -> Result<TwapPrice> {
+        if window_secs == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+
+        let target = now
+            .checked_sub(window_secs as i64)
+            .ok_or(FluxDexError::Underflow)?;
+
+        let start = self
+            .observations
+            .iter()
+            .filter(|observation| observation.timestamp <= target)
+            .max_by_key(|observation| observation.timestamp)
+            .ok_or(FluxDexError::MarketDataUnavailable)?;
+
+        let (price_a_cumulative, price_b_cumulative) = pool.cumulative_prices_at(now)?;
+        let elapsed = (now - start.timestamp) as u128;
+
+        let price_a = price_a_cumulative.wrapping_sub(start.price_a_cumulative) / elapsed;
+        let price_b = price_b_cumulative.wrapping_sub(start.price_b_cumulative) / elapsed;
+
+        Ok(TwapPrice {
+            price_a: FixedPoint::new(price_a),
+            price_b: FixedPoint::new(price_b),
+            window_secs: now - start.timestamp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::CurveType;
+
+    fn empty_observations() -> PriceObservations {
+        PriceObservations {
+            pool: Pubkey::default(),
+            index: 0,
+            observations: Vec::new(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_twap_weights_prices_by_time() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 1_000, 2_000);
+        let mut oracle = empty_observations();
+        oracle.observe(&mut pool, 100).unwrap();
+
+        // 60 秒价格为 2，之后 60 秒价格为 4
+        oracle.observe(&mut pool, 160).unwrap();
+        pool.token_b_reserve = 4_000;
+
+        let twap = oracle.consult(&pool, 220, 120).unwrap();
+        assert_eq!(twap.window_secs, 120);
+        assert_eq!(twap.price_a.to_u64().unwrap(), 3);
+
+        // 起点取不晚于 now - window_secs 的最新观测
+        let twap = oracle.consult(&pool, 220, 60).unwrap();
+        assert_eq!(twap.window_secs, 60);
+        assert_eq!(twap.price_a.to_u64().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_twap_requires_enough_history() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 1_000, 2_000);
+        let mut oracle = empty_observations();
+        oracle.observe(&mut pool, 100).unwrap();
+
+        assert!(oracle.consult(&pool, 150, 60).is_err());
+        assert!(oracle.consult(&pool, 150, 0).is_err());
+    }
+
+    #[test]
+    fn test_observe_records_once_per_second() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 1_000, 2_000);
+        let mut oracle = empty_observations();
+        oracle.observe(&mut pool, 100).unwrap();
+        oracle.observe(&mut pool, 100).unwrap();
+        assert_eq!(oracle.observations.len(), 1);
+
+        oracle.observe(&mut pool, 101).unwrap();
+        assert_eq!(oracle.observations.len(), 2);
+        assert_eq!(oracle.index, 1);
+    }
+}
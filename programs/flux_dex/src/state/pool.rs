@@ -1,4 +1,10 @@
-use crate::utils::FixedPoint;
+use crate::error::FluxDexError;
+use crate::state::{
+    ConcentratedSwapResult, GlobalConfig, LIQUIDITY_SCORE_FULL_DEPTH, MAX_FEE_BPS,
+    MIN_LIQUIDITY_THRESHOLD, VOLATILITY_EWMA_ALPHA_BPS,
+};
+use crate::utils::{ConcentratedMath, FixedPoint, MathUtils, WeightedMath};
+use crate::utils::{ConstantProductCurve, StableSwapCurve, SwapCurve, WeightedCurve};
 use anchor_lang::prelude::*;
 
 /// 流动性池主结构
@@ -17,20 +23,38 @@ pub struct Pool {
     pub token_a_reserve: u64,
     pub token_b_reserve: u64,
 
-    /// LP 代币信息
+    /// LP 代币信息（LP 不可转让，持有账户由 Pool PDA 冻结）
     pub lp_mint: Pubkey,
     pub lp_supply: u64,
 
     /// 费率设置
-    pub base_fee_bps: u16, // 基础费率
+    pub fee_tier: u16, // 费率档位（Pool PDA 种子的一部分，创建后不可变）
+    pub base_fee_bps: u16,         // 基础费率
     pub dynamic_fee_enabled: bool, // 是否启用动态费率
     pub current_fee_bps: u16,      // 当前有效费率
 
+    /// 定价曲线
+    pub curve_type: CurveType, // 定价曲线（Pool PDA 种子的一部分，创建后不可变）
+    pub amp_initial: u64,       // StableSwap 放大系数：调整起点
+    pub amp_target: u64,        // StableSwap 放大系数：调整终点
+    pub amp_ramp_start_ts: i64, // 放大系数开始调整时间
+    pub amp_ramp_stop_ts: i64,  // 放大系数调整结束时间
+    pub weight_a_bps: u16,      // 加权池 Token A 权重
+    pub weight_b_bps: u16,      // 加权池 Token B 权重（两者之和为 10000）
+    pub sqrt_price_x64: u128,   // 集中流动性：当前价格平方根（Q64.64）
+    pub current_tick: i32,      // 集中流动性：当前 tick
+    pub tick_spacing: u16,      // 集中流动性：tick 间距
+    pub liquidity: u128,        // 集中流动性：当前价格下的活跃流动性
+
     /// 智能流动性管理
     pub alm_enabled: bool, // 是否启用智能流动性管理
     pub volatility_score: u16,  // 波动率分数 (0-10000)
     pub last_price_update: i64, // 上次价格更新时间
-    pub price_oracle: Pubkey,   // 价格预言机
+    pub price_oracle: Pubkey,   // 价格预言机（PriceObservations 账户）
+
+    /// 累计价格（价格 × 秒，FixedPoint::SCALE 精度，允许回绕）
+    pub price_a_cumulative: u128,
+    pub price_b_cumulative: u128,
 
     /// MEV 保护
     pub mev_protection_enabled: bool,
@@ -44,11 +68,25 @@ pub struct Pool {
     pub total_fees_collected_a: u64, // 累计手续费A
     pub total_fees_collected_b: u64, // 累计手续费B
     pub swap_count: u64,             // 交易次数
+    pub flash_loan_count: u64,       // 闪电贷次数
+
+    /// 协议费（不计入 LP 储备，等待提取到国库）
+    pub protocol_fees_a: u64,
+    pub protocol_fees_b: u64,
+
+    /// LP 手续费（不计入储备，按 Position 领取）
+    pub fee_growth_global_a: u128, // 每单位 LP 累计手续费 A（FixedPoint::SCALE 精度）
+    pub fee_growth_global_b: u128, // 每单位 LP 累计手续费 B
+    pub unclaimed_lp_fees_a: u64,  // 尚未被领取的 LP 手续费 A
+    pub unclaimed_lp_fees_b: u64,  // 尚未被领取的 LP 手续费 B
 
     /// 治理和控制
     pub paused: bool, // 是否暂停
-    pub emergency_mode: bool,      // 紧急模式
-    pub upgrade_authority: Pubkey, // 升级权限
+    pub emergency_mode: bool,              // 紧急模式
+    pub upgrade_authority: Pubkey,         // 升级权限
+    pub pending_authority: Pubkey,         // 待接受的新管理员（默认值表示没有进行中的移交）
+    pub pending_upgrade_authority: Pubkey, // 待接受的新升级权限
+    pub locked: bool,                      // 重入锁：闪电贷回调期间禁止兑换和流动性变更
 
     /// 元数据
     pub created_at: i64,
@@ -62,27 +100,769 @@ impl Pool {
         32 + 32 + 32 + 32 + // token mints and vaults
         8 + 8 + // reserves
         32 + 8 + // lp mint and supply
-        2 + 1 + 2 + // fee settings
+        2 + 2 + 1 + 2 + // fee settings
+        1 + 8 + 8 + 8 + 8 + 2 + 2 + // curve settings
+        16 + 4 + 2 + 16 + // concentrated liquidity
         1 + 2 + 8 + 32 + // ALM settings
+        16 + 16 + // cumulative prices
         1 + 32 + 1 + 2 + // MEV protection
-        16 + 16 + 8 + 8 + 8 + // statistics
-        1 + 1 + 32 + // governance
+        16 + 16 + 8 + 8 + 8 + 8 + // statistics
+        8 + 8 + // protocol fees
+        16 + 16 + 8 + 8 + // lp fee growth
+        1 + 1 + 32 + 1 + 32 + 32 + // governance
         8 + 8 + 1 + // metadata
         64; // padding for future upgrades
 
-    /// 获取当前价格
-    pub fn get_current_price(&self) -> FixedPoint {
-        if self.token_a_reserve == 0 {
-            return FixedPoint::from_u64(0);
+    /// 获取当前价格（以 Token B 计价的 Token A 价格）
+    pub fn get_current_price(&self, now: i64) -> FixedPoint {
+        self.spot_price(true, now)
+    }
+
+    /// 现价，由池子曲线计算；集中流动性池取 sqrt_price 的平方
+    pub fn spot_price(&self, a_to_b: bool, now: i64) -> FixedPoint {
+        if self.curve_type == CurveType::Concentrated {
+            let price_a = ConcentratedMath::sqrt_price_to_price(self.sqrt_price_x64)
+                .unwrap_or(FixedPoint::new(u128::MAX));
+            if a_to_b || price_a.value == 0 {
+                return price_a;
+            }
+            return FixedPoint::new(
+                MathUtils::mul_div(FixedPoint::SCALE, FixedPoint::SCALE, price_a.value)
+                    .unwrap_or(u128::MAX),
+            );
+        }
+
+        // StableSwap 的现价取决于当前放大系数
+        match self.swap_curve(now) {
+            Ok(curve) => curve.spot_price(self.token_a_reserve, self.token_b_reserve, a_to_b),
+            Err(_) => FixedPoint::from_u64(0),
+        }
+    }
+
+    /// 按 curve_type 构造池子的兑换曲线
+    ///
+    /// 集中流动性需要沿 tick 逐段计算，不基于储备量定价，见 compute_concentrated_swap
+    pub fn swap_curve(&self, now: i64) -> Result<Box<dyn SwapCurve>> {
+        match self.curve_type {
+            CurveType::ConstantProduct => Ok(Box::new(ConstantProductCurve)),
+            CurveType::StableSwap => Ok(Box::new(StableSwapCurve {
+                amp: self.current_amp(now),
+            })),
+            CurveType::Weighted => Ok(Box::new(WeightedCurve {
+                weight_a_bps: self.weight_a_bps,
+                weight_b_bps: self.weight_b_bps,
+            })),
+            CurveType::Concentrated => Err(FluxDexError::InvalidCurveType.into()),
+        }
+    }
+
+    /// 当前放大系数：在调整区间内线性插值
+    pub fn current_amp(&self, now: i64) -> u64 {
+        if now >= self.amp_ramp_stop_ts || self.amp_ramp_stop_ts <= self.amp_ramp_start_ts {
+            return self.amp_target;
+        }
+
+        let elapsed = now.saturating_sub(self.amp_ramp_start_ts).max(0) as u128;
+        let duration = (self.amp_ramp_stop_ts - self.amp_ramp_start_ts) as u128;
+        let (initial, target) = (self.amp_initial as u128, self.amp_target as u128);
+
+        let amp = if target > initial {
+            initial + (target - initial) * elapsed / duration
+        } else {
+            initial - (initial - target) * elapsed / duration
+        };
+        amp as u64
+    }
+
+    /// 按池子曲线计算兑换输出量
+    pub fn calculate_swap_output(
+        &self,
+        amount_in: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+        now: i64,
+    ) -> Result<u64> {
+        self.swap_curve(now)?.swap_output(
+            amount_in,
+            self.token_a_reserve,
+            self.token_b_reserve,
+            a_to_b,
+            fee_bps,
+        )
+    }
+
+    /// 按池子曲线计算获得指定输出量所需的输入量
+    pub fn calculate_swap_input(
+        &self,
+        amount_out: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+        now: i64,
+    ) -> Result<u64> {
+        self.swap_curve(now)?.swap_input(
+            amount_out,
+            self.token_a_reserve,
+            self.token_b_reserve,
+            a_to_b,
+            fee_bps,
+        )
+    }
+
+    /// 计算存入应铸造的 LP 数量
+    ///
+    /// 首次存入按各曲线的不变量铸造，之后按比例铸造；集中流动性按区间头寸记账，不发行 LP 代币
+    pub fn calculate_lp_tokens(&self, deposit_a: u64, deposit_b: u64, now: i64) -> Result<u64> {
+        self.swap_curve(now)?.deposit_lp_tokens(
+            deposit_a,
+            deposit_b,
+            self.token_a_reserve,
+            self.token_b_reserve,
+            self.lp_supply,
+        )
+    }
+
+    /// 计算赎回 lp_tokens 可取回的资产数量
+    pub fn calculate_withdraw_amounts(&self, lp_tokens: u64, now: i64) -> Result<(u64, u64)> {
+        self.swap_curve(now)?.withdraw_amounts(
+            lp_tokens,
+            self.token_a_reserve,
+            self.token_b_reserve,
+            self.lp_supply,
+        )
+    }
+
+    /// 单边存入时需要先在池内兑换的数量，使剩余部分与兑换所得恰好符合兑换后的储备比例
+    ///
+    /// 恒定乘积有闭式解；StableSwap 在 [0, amount_in] 上二分，取剩余部分不少于配平所需的最大兑换量
+    pub fn calculate_zap_swap_amount(
+        &self,
+        amount_in: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+        now: i64,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = self.reserves_for(a_to_b);
+        match self.curve_type {
+            CurveType::ConstantProduct => {
+                MathUtils::calculate_zap_swap_amount(amount_in, reserve_in, fee_bps)
+            }
+            CurveType::StableSwap => {
+                if amount_in == 0 {
+                    return Err(FluxDexError::InvalidInputAmount.into());
+                }
+                let curve = self.swap_curve(now)?;
+                let (mut low, mut high) = (0u64, amount_in);
+                while high - low > 1 {
+                    let mid = low + (high - low) / 2;
+                    let output = curve.swap_output(
+                        mid,
+                        self.token_a_reserve,
+                        self.token_b_reserve,
+                        a_to_b,
+                        fee_bps,
+                    )?;
+                    let fee_amount = MathUtils::calculate_fee_amount(mid, fee_bps)?;
+                    let new_reserve_in = reserve_in as u128 + (mid - fee_amount) as u128;
+                    let new_reserve_out = reserve_out
+                        .checked_sub(output)
+                        .ok_or(FluxDexError::InsufficientLiquidity)?
+                        as u128;
+
+                    // 剩余部分 / 兑换所得 不低于兑换后的储备比例时还可以多兑换
+                    let remaining_value = ((amount_in - mid) as u128)
+                        .checked_mul(new_reserve_out)
+                        .ok_or(FluxDexError::Overflow)?;
+                    let output_value = (output as u128)
+                        .checked_mul(new_reserve_in)
+                        .ok_or(FluxDexError::Overflow)?;
+                    if remaining_value >= output_value {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                Ok(low)
+            }
+            _ => Err(FluxDexError::InvalidCurveType.into()),
+        }
+    }
+
+    /// 加权池单资产加入，返回 (铸造的 LP 数量, 手续费)
+    pub fn calculate_single_sided_lp(
+        &self,
+        amount_in: u64,
+        is_token_a: bool,
+        fee_bps: u16,
+    ) -> Result<(u64, u64)> {
+        if self.curve_type != CurveType::Weighted {
+            return Err(FluxDexError::InvalidCurveType.into());
+        }
+        let (reserve_in, _) = self.reserves_for(is_token_a);
+        let (weight_in, _) = self.weights_for(is_token_a);
+        WeightedMath::calculate_single_asset_lp(
+            amount_in,
+            reserve_in,
+            weight_in,
+            self.lp_supply,
+            fee_bps,
+        )
+    }
+
+    /// 按兑换方向返回 (输入权重, 输出权重)
+    pub fn weights_for(&self, a_to_b: bool) -> (u16, u16) {
+        if a_to_b {
+            (self.weight_a_bps, self.weight_b_bps)
+        } else {
+            (self.weight_b_bps, self.weight_a_bps)
+        }
+    }
+
+    /// 按兑换方向返回 (输入储备, 输出储备)
+    pub fn reserves_for(&self, a_to_b: bool) -> (u64, u64) {
+        if a_to_b {
+            (self.token_a_reserve, self.token_b_reserve)
+        } else {
+            (self.token_b_reserve, self.token_a_reserve)
         }
-        let price =
-            (self.token_b_reserve as u128 * FixedPoint::SCALE) / self.token_a_reserve as u128;
-        FixedPoint::new(price)
+    }
+
+    /// 计算到 now 为止的累计价格（不修改状态）
+    pub fn cumulative_prices_at(&self, now: i64) -> Result<(u128, u128)> {
+        let elapsed = now.saturating_sub(self.last_price_update);
+        if elapsed <= 0 || self.token_a_reserve == 0 || self.token_b_reserve == 0 {
+            return Ok((self.price_a_cumulative, self.price_b_cumulative));
+        }
+
+        let price_a = self.spot_price(true, now).value;
+        let price_b = self.spot_price(false, now).value;
+
+        Ok((
+            self.price_a_cumulative
+                .wrapping_add(price_a.wrapping_mul(elapsed as u128)),
+            self.price_b_cumulative
+                .wrapping_add(price_b.wrapping_mul(elapsed as u128)),
+        ))
+    }
+
+    /// 以变化前的储备量累计价格，须在每次储备量变化之前调用
+    pub fn update_price_accumulators(&mut self, now: i64) -> Result<()> {
+        let (price_a_cumulative, price_b_cumulative) = self.cumulative_prices_at(now)?;
+        self.price_a_cumulative = price_a_cumulative;
+        self.price_b_cumulative = price_b_cumulative;
+        if now > self.last_price_update {
+            self.last_price_update = now;
+        }
+        Ok(())
+    }
+
+    /// 根据波动率与流动性重新计算动态费率，返回流动性分数
+    ///
+    /// reference_price 为波动率的基准价格（通常为 TWAP）
+    pub fn refresh_dynamic_fee(&mut self, reference_price: FixedPoint, now: i64) -> Result<u16> {
+        self.volatility_score = MathUtils::calculate_volatility_ewma(
+            self.volatility_score,
+            reference_price,
+            self.get_current_price(now),
+            VOLATILITY_EWMA_ALPHA_BPS,
+        )?;
+
+        let liquidity_score = MathUtils::calculate_liquidity_score(
+            self.token_a_reserve,
+            self.token_b_reserve,
+            LIQUIDITY_SCORE_FULL_DEPTH,
+        )?;
+
+        self.current_fee_bps = MathUtils::calculate_dynamic_fee(
+            self.base_fee_bps,
+            self.volatility_score,
+            liquidity_score,
+        )?
+        .min(MAX_FEE_BPS);
+
+        Ok(liquidity_score)
+    }
+
+    /// 记录一笔兑换：更新储备量与统计数据
+    ///
+    /// 手续费不计入储备，由 credit_fee 分配给协议与 LP
+    pub fn record_swap(
+        &mut self,
+        a_to_b: bool,
+        amount_in: u64,
+        amount_out: u64,
+        fee_amount: u64,
+        protocol_fee_bps: u16,
+    ) -> Result<()> {
+        let amount_in_to_reserve = amount_in
+            .checked_sub(fee_amount)
+            .ok_or(FluxDexError::Underflow)?;
+        self.credit_fee(a_to_b, fee_amount, protocol_fee_bps)?;
+
+        let (reserve_in, reserve_out) = if a_to_b {
+            (&mut self.token_a_reserve, &mut self.token_b_reserve)
+        } else {
+            (&mut self.token_b_reserve, &mut self.token_a_reserve)
+        };
+        *reserve_in = reserve_in
+            .checked_add(amount_in_to_reserve)
+            .ok_or(FluxDexError::Overflow)?;
+        *reserve_out = reserve_out
+            .checked_sub(amount_out)
+            .ok_or(FluxDexError::InsufficientLiquidity)?;
+
+        self.record_volume(a_to_b, amount_in, amount_out)
+    }
+
+    /// 记录一笔集中流动性兑换：写回价格、tick、活跃流动性与手续费增长
+    ///
+    /// LP 手续费已在兑换时按每段的活跃流动性计入 fee_growth_global
+    pub fn record_concentrated_swap(
+        &mut self,
+        a_to_b: bool,
+        result: &ConcentratedSwapResult,
+    ) -> Result<()> {
+        let amount_in_to_reserve = result
+            .amount_in
+            .checked_sub(result.fee_amount)
+            .ok_or(FluxDexError::Underflow)?;
+        let lp_fee = result
+            .fee_amount
+            .checked_sub(result.protocol_fee)
+            .ok_or(FluxDexError::Underflow)?;
+
+        let (reserve_in, reserve_out, protocol_fees, unclaimed_lp_fees, fee_growth, total_fees) =
+            if a_to_b {
+                (
+                    &mut self.token_a_reserve,
+                    &mut self.token_b_reserve,
+                    &mut self.protocol_fees_a,
+                    &mut self.unclaimed_lp_fees_a,
+                    &mut self.fee_growth_global_a,
+                    &mut self.total_fees_collected_a,
+                )
+            } else {
+                (
+                    &mut self.token_b_reserve,
+                    &mut self.token_a_reserve,
+                    &mut self.protocol_fees_b,
+                    &mut self.unclaimed_lp_fees_b,
+                    &mut self.fee_growth_global_b,
+                    &mut self.total_fees_collected_b,
+                )
+            };
+        *reserve_in = reserve_in
+            .checked_add(amount_in_to_reserve)
+            .ok_or(FluxDexError::Overflow)?;
+        *reserve_out = reserve_out
+            .checked_sub(result.amount_out)
+            .ok_or(FluxDexError::InsufficientLiquidity)?;
+        *protocol_fees = protocol_fees
+            .checked_add(result.protocol_fee)
+            .ok_or(FluxDexError::Overflow)?;
+        *unclaimed_lp_fees = unclaimed_lp_fees
+            .checked_add(lp_fee)
+            .ok_or(FluxDexError::Overflow)?;
+        *fee_growth = result.fee_growth_global_in;
+        *total_fees = total_fees
+            .checked_add(result.fee_amount)
+            .ok_or(FluxDexError::Overflow)?;
+
+        self.sqrt_price_x64 = result.sqrt_price_x64;
+        self.current_tick = result.current_tick;
+        self.liquidity = result.liquidity;
+
+        self.record_volume(a_to_b, result.amount_in, result.amount_out)
+    }
+
+    fn record_volume(&mut self, a_to_b: bool, amount_in: u64, amount_out: u64) -> Result<()> {
+        let (volume_a, volume_b) = if a_to_b {
+            (amount_in, amount_out)
+        } else {
+            (amount_out, amount_in)
+        };
+        self.total_volume_a = self
+            .total_volume_a
+            .checked_add(volume_a as u128)
+            .ok_or(FluxDexError::Overflow)?;
+        self.total_volume_b = self
+            .total_volume_b
+            .checked_add(volume_b as u128)
+            .ok_or(FluxDexError::Overflow)?;
+
+        self.swap_count = self
+            .swap_count
+            .checked_add(1)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok(())
+    }
+
+    /// 由 Position 持有、可以领取手续费的 LP 数量（扣除首次存入时永久锁定的部分）
+    pub fn claimable_lp_supply(&self) -> u64 {
+        self.lp_supply.saturating_sub(MIN_LIQUIDITY_THRESHOLD)
+    }
+
+    /// 分配一笔手续费：protocol_fee_bps 比例记入协议费，其余计入 LP 手续费增长
+    pub fn credit_fee(
+        &mut self,
+        is_token_a: bool,
+        fee_amount: u64,
+        protocol_fee_bps: u16,
+    ) -> Result<()> {
+        let mut protocol_fee = MathUtils::calculate_fee_amount(fee_amount, protocol_fee_bps)?;
+        let mut lp_fee = fee_amount
+            .checked_sub(protocol_fee)
+            .ok_or(FluxDexError::Underflow)?;
+
+        // 永久锁定的 LP 没有 Position，不能领取手续费，因此不计入分母；
+        // 没有可领取的 LP 时整笔 LP 手续费记入协议费
+        let claimable_lp_supply = self.claimable_lp_supply();
+        if claimable_lp_supply == 0 {
+            protocol_fee = fee_amount;
+            lp_fee = 0;
+        }
+
+        // 每单位 LP 的手续费增长，累加器允许回绕，差值用 wrapping_sub 计算
+        let fee_growth = if claimable_lp_supply > 0 {
+            (lp_fee as u128)
+                .checked_mul(FixedPoint::SCALE)
+                .ok_or(FluxDexError::Overflow)?
+                / claimable_lp_supply as u128
+        } else {
+            0
+        };
+
+        let (protocol_fees, unclaimed_lp_fees, fee_growth_global, total_fees_collected) =
+            if is_token_a {
+                (
+                    &mut self.protocol_fees_a,
+                    &mut self.unclaimed_lp_fees_a,
+                    &mut self.fee_growth_global_a,
+                    &mut self.total_fees_collected_a,
+                )
+            } else {
+                (
+                    &mut self.protocol_fees_b,
+                    &mut self.unclaimed_lp_fees_b,
+                    &mut self.fee_growth_global_b,
+                    &mut self.total_fees_collected_b,
+                )
+            };
+        *protocol_fees = protocol_fees
+            .checked_add(protocol_fee)
+            .ok_or(FluxDexError::Overflow)?;
+        *unclaimed_lp_fees = unclaimed_lp_fees
+            .checked_add(lp_fee)
+            .ok_or(FluxDexError::Overflow)?;
+        *fee_growth_global = fee_growth_global.wrapping_add(fee_growth);
+        *total_fees_collected = total_fees_collected
+            .checked_add(fee_amount)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok(())
+    }
+
+    /// 记录一笔闪电贷：手续费直接计入储备，由全体 LP 按份额共享
+    pub fn record_flash_loan(&mut self, fee_a: u64, fee_b: u64) -> Result<()> {
+        self.token_a_reserve = self
+            .token_a_reserve
+            .checked_add(fee_a)
+            .ok_or(FluxDexError::Overflow)?;
+        self.token_b_reserve = self
+            .token_b_reserve
+            .checked_add(fee_b)
+            .ok_or(FluxDexError::Overflow)?;
+
+        self.total_fees_collected_a = self
+            .total_fees_collected_a
+            .checked_add(fee_a)
+            .ok_or(FluxDexError::Overflow)?;
+        self.total_fees_collected_b = self
+            .total_fees_collected_b
+            .checked_add(fee_b)
+            .ok_or(FluxDexError::Overflow)?;
+
+        self.flash_loan_count = self
+            .flash_loan_count
+            .checked_add(1)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok(())
+    }
+
+    /// 读取管理开关的当前生效值；ALM 与 MEV 保护同时受全局开关约束
+    pub fn flag(&self, flag: PoolFlag, config: &GlobalConfig) -> bool {
+        match flag {
+            PoolFlag::Paused => self.paused,
+            PoolFlag::EmergencyMode => self.emergency_mode,
+            PoolFlag::DynamicFee => self.dynamic_fee_enabled,
+            PoolFlag::Alm => self.alm_active(config),
+            PoolFlag::MevProtection => self.mev_protection_active(config),
+        }
+    }
+
+    /// 设置管理开关；关闭动态费率时当前费率回到基础费率
+    pub fn set_flag(&mut self, flag: PoolFlag, enabled: bool) {
+        match flag {
+            PoolFlag::Paused => self.paused = enabled,
+            PoolFlag::EmergencyMode => self.emergency_mode = enabled,
+            PoolFlag::DynamicFee => {
+                self.dynamic_fee_enabled = enabled;
+                if !enabled {
+                    self.current_fee_bps = self.base_fee_bps;
+                }
+            }
+            PoolFlag::Alm => self.alm_enabled = enabled,
+            PoolFlag::MevProtection => self.mev_protection_enabled = enabled,
+        }
+    }
+
+    /// 角色当前的持有者
+    pub fn role_holder(&self, role: AuthorityRole) -> Pubkey {
+        match role {
+            AuthorityRole::Authority => self.authority,
+            AuthorityRole::UpgradeAuthority => self.upgrade_authority,
+        }
+    }
+
+    /// 角色待接受的新持有者
+    pub fn pending_role_holder(&self, role: AuthorityRole) -> Pubkey {
+        match role {
+            AuthorityRole::Authority => self.pending_authority,
+            AuthorityRole::UpgradeAuthority => self.pending_upgrade_authority,
+        }
+    }
+
+    /// 设置角色待接受的新持有者，Pubkey::default() 表示取消
+    pub fn set_pending_role_holder(&mut self, role: AuthorityRole, pending: Pubkey) {
+        match role {
+            AuthorityRole::Authority => self.pending_authority = pending,
+            AuthorityRole::UpgradeAuthority => self.pending_upgrade_authority = pending,
+        }
+    }
+
+    /// 完成角色移交：待接受者成为持有者并清空待接受状态
+    pub fn accept_role(&mut self, role: AuthorityRole) {
+        let pending = self.pending_role_holder(role);
+        match role {
+            AuthorityRole::Authority => self.authority = pending,
+            AuthorityRole::UpgradeAuthority => self.upgrade_authority = pending,
+        }
+        self.set_pending_role_holder(role, Pubkey::default());
+    }
+
+    /// 兑换与存取流动性的前置检查：未处于紧急模式、未暂停、未被闪电贷锁定
+    pub fn check_operational(&self) -> Result<()> {
+        self.check_not_emergency()?;
+        if self.paused {
+            return Err(FluxDexError::PoolPaused.into());
+        }
+        self.check_unlocked()
+    }
+
+    /// 紧急模式下只允许 emergency_withdraw
+    pub fn check_not_emergency(&self) -> Result<()> {
+        if self.emergency_mode {
+            return Err(FluxDexError::EmergencyModeActive.into());
+        }
+        Ok(())
+    }
+
+    /// 闪电贷进行中时拒绝会读写储备或金库的操作，防止回调重入
+    pub fn check_unlocked(&self) -> Result<()> {
+        if self.locked {
+            return Err(FluxDexError::PoolLocked.into());
+        }
+        Ok(())
+    }
+
+    /// 金库中应属于 LP 储备、协议费与未领取 LP 手续费的数量
+    pub fn owed_balances(&self) -> Result<(u64, u64)> {
+        let owed_a = self
+            .token_a_reserve
+            .checked_add(self.protocol_fees_a)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_add(self.unclaimed_lp_fees_a)
+            .ok_or(FluxDexError::Overflow)?;
+        let owed_b = self
+            .token_b_reserve
+            .checked_add(self.protocol_fees_b)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_add(self.unclaimed_lp_fees_b)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok((owed_a, owed_b))
+    }
+
+    /// 储备量不变式：储备 + 待提取费用不能超过金库实际余额
+    pub fn check_reserves_backed(&self, vault_a_amount: u64, vault_b_amount: u64) -> Result<()> {
+        let (owed_a, owed_b) = self.owed_balances()?;
+        if owed_a > vault_a_amount || owed_b > vault_b_amount {
+            return Err(FluxDexError::ReserveExceedsVaultBalance.into());
+        }
+        Ok(())
+    }
+
+    /// ALM 是否生效：池子开关与全局开关须同时开启，全局关闭后已开启的池子随之失效
+    pub fn alm_active(&self, config: &GlobalConfig) -> bool {
+        self.alm_enabled && config.alm_enabled_globally
+    }
+
+    /// MEV 保护是否生效：池子开关与全局开关须同时开启
+    pub fn mev_protection_active(&self, config: &GlobalConfig) -> bool {
+        self.mev_protection_enabled && config.mev_protection_enabled_globally
     }
 
     /// 检查是否需要重新平衡
-    pub fn needs_rebalance(&self) -> bool {
+    pub fn needs_rebalance(&self, config: &GlobalConfig) -> bool {
+        if !self.alm_active(config) {
+            return false;
+        }
         // 实现重新平衡逻辑
         false // placeholder
     }
 }
+
+/// 池子定价曲线
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CurveType {
+    /// 恒定乘积 (x * y = k)
+    ConstantProduct,
+    /// Curve StableSwap，适用于稳定币与 LST 交易对
+    StableSwap,
+    /// 加权乘积 (Π B_i^w_i = k)，如 80/20 池
+    Weighted,
+    /// 集中流动性，LP 在价格区间内提供流动性
+    Concentrated,
+}
+
+/// 池子管理员可切换的开关
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PoolFlag {
+    /// 暂停兑换与流动性变更
+    Paused,
+    /// 紧急模式
+    EmergencyMode,
+    /// 动态费率
+    DynamicFee,
+    /// 智能流动性管理
+    Alm,
+    /// MEV 保护
+    MevProtection,
+}
+
+/// 可两步移交的池子角色
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AuthorityRole {
+    /// 池子管理员
+    Authority,
+    /// 升级权限
+    UpgradeAuthority,
+}
+
+#[cfg(test)]
+impl Pool {
+    /// 测试用池子：其余字段为零，StableSwap 放大系数固定为 100
+    pub(crate) fn for_test(curve_type: CurveType, reserve_a: u64, reserve_b: u64) -> Pool {
+        let mut pool = Pool::deserialize(&mut &[0u8; Pool::SIZE][..]).unwrap();
+        pool.curve_type = curve_type;
+        pool.amp_initial = 100;
+        pool.amp_target = 100;
+        pool.token_a_reserve = reserve_a;
+        pool.token_b_reserve = reserve_b;
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zap_leaves_deposit_in_pool_ratio() {
+        for (curve_type, reserve_a, reserve_b) in [
+            (CurveType::ConstantProduct, 1_000_000, 1_000_000),
+            (CurveType::ConstantProduct, 1_000_000, 3_000_000),
+            (CurveType::StableSwap, 1_000_000, 1_000_000),
+            (CurveType::StableSwap, 1_000_000, 3_000_000),
+        ] {
+            let pool = Pool::for_test(curve_type, reserve_a, reserve_b);
+            let amount_in = 200_000;
+            let swap_amount = pool
+                .calculate_zap_swap_amount(amount_in, true, 30, 0)
+                .unwrap();
+            let output = pool
+                .calculate_swap_output(swap_amount, true, 30, 0)
+                .unwrap();
+
+            let fee = MathUtils::calculate_fee_amount(swap_amount, 30).unwrap();
+            let new_reserve_a = (reserve_a + swap_amount - fee) as u128;
+            let new_reserve_b = (reserve_b - output) as u128;
+            let remaining = (amount_in - swap_amount) as u128;
+
+            // 剩余部分与兑换所得按兑换后的储备比例配平，误差不超过一次兑换 1 个单位的影响
+            let balanced_remaining = output as u128 * new_reserve_a / new_reserve_b;
+            assert!(remaining >= balanced_remaining);
+            assert!(remaining - balanced_remaining <= 2);
+        }
+    }
+
+    #[test]
+    fn test_locked_pool_rejects_state_changes() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 1_000_000, 1_000_000);
+        pool.locked = true;
+
+        // 兑换、增减流动性与闪电贷走 check_operational，sync/skim/领取手续费走 check_unlocked
+        let locked: Error = FluxDexError::PoolLocked.into();
+        assert_eq!(pool.check_operational().unwrap_err(), locked);
+        assert_eq!(pool.check_unlocked().unwrap_err(), locked);
+
+        // 紧急模式优先于锁
+        pool.emergency_mode = true;
+        let emergency: Error = FluxDexError::EmergencyModeActive.into();
+        assert_eq!(pool.check_operational().unwrap_err(), emergency);
+
+        pool.emergency_mode = false;
+        pool.locked = false;
+        assert!(pool.check_operational().is_ok());
+    }
+
+    #[test]
+    fn test_flash_loan_fee_goes_to_reserves() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 1_000_000, 2_000_000);
+        pool.record_flash_loan(300, 0).unwrap();
+        pool.record_flash_loan(0, 7).unwrap();
+
+        assert_eq!(pool.token_a_reserve, 1_000_300);
+        assert_eq!(pool.token_b_reserve, 2_000_007);
+        assert_eq!(pool.total_fees_collected_a, 300);
+        assert_eq!(pool.total_fees_collected_b, 7);
+        assert_eq!(pool.flash_loan_count, 2);
+        // 闪电贷手续费由全体 LP 按份额共享，不经过 fee_growth
+        assert_eq!(pool.unclaimed_lp_fees_a, 0);
+        assert_eq!(pool.fee_growth_global_a, 0);
+    }
+
+    #[test]
+    fn test_zap_rejects_unsupported_curves() {
+        let pool = Pool::for_test(CurveType::Concentrated, 1_000_000, 1_000_000);
+        assert!(pool.calculate_zap_swap_amount(1_000, true, 30, 0).is_err());
+    }
+
+    #[test]
+    fn test_feature_flags_require_global_switch() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 1_000_000, 1_000_000);
+        let mut config = GlobalConfig::deserialize(&mut &[0u8; GlobalConfig::SIZE][..]).unwrap();
+        pool.alm_enabled = true;
+        pool.mev_protection_enabled = true;
+
+        // 全局关闭时池子自身的开关不生效
+        assert!(!pool.flag(PoolFlag::Alm, &config));
+        assert!(!pool.flag(PoolFlag::MevProtection, &config));
+        assert!(!pool.needs_rebalance(&config));
+
+        config.alm_enabled_globally = true;
+        config.mev_protection_enabled_globally = true;
+        assert!(pool.flag(PoolFlag::Alm, &config));
+        assert!(pool.flag(PoolFlag::MevProtection, &config));
+
+        pool.alm_enabled = false;
+        assert!(!pool.flag(PoolFlag::Alm, &config));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::utils::FixedPoint;
+use crate::error::FluxDexError;
+use crate::state::Pool;
+use crate::utils::{ConcentratedMath, FixedPoint, MathUtils};
 use anchor_lang::prelude::*;
 
 /// 用户流动性头寸
@@ -33,6 +35,19 @@ pub struct Position {
     pub mev_rewards_earned: u64,
     pub mev_rewards_claimed: u64,
 
+    /// 手续费领取记录
+    pub fee_growth_checkpoint_a: u128, // 上次结算时的 Pool.fee_growth_global_a
+    pub fee_growth_checkpoint_b: u128, // 上次结算时的 Pool.fee_growth_global_b
+    pub fees_owed_a: u64,              // 已结算未领取的手续费 A
+    pub fees_owed_b: u64,              // 已结算未领取的手续费 B
+
+    /// 集中流动性区间头寸
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    pub liquidity: u128,
+    pub fee_growth_inside_checkpoint_a: u128, // 上次结算时区间内的手续费增长 A
+    pub fee_growth_inside_checkpoint_b: u128, // 上次结算时区间内的手续费增长 B
+
     /// 元数据
     pub created_at: i64,
     pub last_rebalanced: i64,
@@ -46,8 +61,221 @@ impl Position {
         1 + 1 + 32 + 32 + 1 + 2 + // strategy settings
         8 + 8 + 8 + 8 + 8 + 8 + // pnl tracking
         8 + 8 + // mev rewards
+        16 + 16 + 8 + 8 + // fee checkpoints
+        4 + 4 + 16 + 16 + 16 + // concentrated range
         8 + 8 + 1 + // metadata
         32; // padding
+
+    /// 记录存入流动性：首次存入时初始化 Position，否则累加
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_deposit(
+        &mut self,
+        owner: Pubkey,
+        pool: Pubkey,
+        amount_a: u64,
+        amount_b: u64,
+        lp_tokens: u64,
+        bump: u8,
+        now: i64,
+    ) -> Result<()> {
+        if self.lp_tokens == 0 {
+            // 首次添加流动性，初始化 Position
+            self.initialize(owner, pool, bump, now);
+        }
+        self.add_initial_deposit(amount_a, amount_b)?;
+
+        self.lp_tokens = self
+            .lp_tokens
+            .checked_add(lp_tokens)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok(())
+    }
+
+    /// 开立集中流动性区间头寸，须在 Position 没有流动性时调用
+    pub fn open_range(
+        &mut self,
+        owner: Pubkey,
+        pool: Pubkey,
+        tick_lower: i32,
+        tick_upper: i32,
+        bump: u8,
+        now: i64,
+    ) -> Result<()> {
+        self.initialize(owner, pool, bump, now);
+        self.strategy_type = StrategyType::RangeMarketMaking;
+        self.tick_lower_index = tick_lower;
+        self.tick_upper_index = tick_upper;
+        self.price_range_lower = ConcentratedMath::sqrt_price_to_price(
+            ConcentratedMath::sqrt_price_at_tick(tick_lower)?,
+        )?;
+        self.price_range_upper = ConcentratedMath::sqrt_price_to_price(
+            ConcentratedMath::sqrt_price_at_tick(tick_upper)?,
+        )?;
+        Ok(())
+    }
+
+    /// 记录区间流动性存入
+    pub fn record_range_deposit(
+        &mut self,
+        amount_a: u64,
+        amount_b: u64,
+        liquidity: u128,
+    ) -> Result<()> {
+        self.add_initial_deposit(amount_a, amount_b)?;
+        self.liquidity = self
+            .liquidity
+            .checked_add(liquidity)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok(())
+    }
+
+    /// 记录区间流动性赎回，并按比例缩减初始存入量
+    pub fn record_range_withdrawal(&mut self, liquidity: u128) -> Result<()> {
+        if liquidity > self.liquidity {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let remaining = self.liquidity - liquidity;
+        self.initial_deposit_a =
+            MathUtils::mul_div(self.initial_deposit_a as u128, remaining, self.liquidity)? as u64;
+        self.initial_deposit_b =
+            MathUtils::mul_div(self.initial_deposit_b as u128, remaining, self.liquidity)? as u64;
+        self.liquidity = remaining;
+        Ok(())
+    }
+
+    /// 按区间内手续费增长结算应得手续费，须在 liquidity 变化前调用
+    pub fn accrue_range_fees(
+        &mut self,
+        fee_growth_inside_a: u128,
+        fee_growth_inside_b: u128,
+    ) -> Result<()> {
+        let owed_a = MathUtils::mul_div(
+            fee_growth_inside_a.wrapping_sub(self.fee_growth_inside_checkpoint_a),
+            self.liquidity,
+            FixedPoint::SCALE,
+        )?;
+        let owed_b = MathUtils::mul_div(
+            fee_growth_inside_b.wrapping_sub(self.fee_growth_inside_checkpoint_b),
+            self.liquidity,
+            FixedPoint::SCALE,
+        )?;
+
+        self.fees_owed_a = self
+            .fees_owed_a
+            .checked_add(u64::try_from(owed_a).map_err(|_| FluxDexError::Overflow)?)
+            .ok_or(FluxDexError::Overflow)?;
+        self.fees_owed_b = self
+            .fees_owed_b
+            .checked_add(u64::try_from(owed_b).map_err(|_| FluxDexError::Overflow)?)
+            .ok_or(FluxDexError::Overflow)?;
+        self.fee_growth_inside_checkpoint_a = fee_growth_inside_a;
+        self.fee_growth_inside_checkpoint_b = fee_growth_inside_b;
+        Ok(())
+    }
+
+    fn initialize(&mut self, owner: Pubkey, pool: Pubkey, bump: u8, now: i64) {
+        self.owner = owner;
+        self.pool = pool;
+        self.strategy_type = StrategyType::Passive; // 默认被动策略
+        self.risk_profile = RiskProfile::Balanced; // 默认平衡风险
+        self.auto_rebalance = false;
+        self.rebalance_threshold_bps = 500; // 5% 阈值
+
+        self.initial_deposit_a = 0;
+        self.initial_deposit_b = 0;
+        self.realized_fees_a = 0;
+        self.realized_fees_b = 0;
+        self.unrealized_pnl_a = 0;
+        self.unrealized_pnl_b = 0;
+
+        self.mev_rewards_earned = 0;
+        self.mev_rewards_claimed = 0;
+
+        self.created_at = now;
+        self.last_rebalanced = now;
+        self.bump = bump;
+    }
+
+    fn add_initial_deposit(&mut self, amount_a: u64, amount_b: u64) -> Result<()> {
+        self.initial_deposit_a = self
+            .initial_deposit_a
+            .checked_add(amount_a)
+            .ok_or(FluxDexError::Overflow)?;
+        self.initial_deposit_b = self
+            .initial_deposit_b
+            .checked_add(amount_b)
+            .ok_or(FluxDexError::Overflow)?;
+        Ok(())
+    }
+
+    /// 是否可以关闭：不再持有 LP 或区间流动性，且没有未领取的 MEV 收益或手续费
+    pub fn is_empty(&self) -> bool {
+        self.lp_tokens == 0
+            && self.liquidity == 0
+            && self.mev_rewards_earned <= self.mev_rewards_claimed
+            && self.fees_owed_a == 0
+            && self.fees_owed_b == 0
+    }
+
+    /// 按池子当前手续费增长结算应得手续费，须在 lp_tokens 变化前调用
+    pub fn accrue_fees(&mut self, pool: &Pool) -> Result<()> {
+        let owed_a = Self::fees_since(
+            self.lp_tokens,
+            pool.fee_growth_global_a,
+            self.fee_growth_checkpoint_a,
+        )?;
+        let owed_b = Self::fees_since(
+            self.lp_tokens,
+            pool.fee_growth_global_b,
+            self.fee_growth_checkpoint_b,
+        )?;
+
+        self.fees_owed_a = self
+            .fees_owed_a
+            .checked_add(owed_a)
+            .ok_or(FluxDexError::Overflow)?;
+        self.fees_owed_b = self
+            .fees_owed_b
+            .checked_add(owed_b)
+            .ok_or(FluxDexError::Overflow)?;
+        self.fee_growth_checkpoint_a = pool.fee_growth_global_a;
+        self.fee_growth_checkpoint_b = pool.fee_growth_global_b;
+        Ok(())
+    }
+
+    fn fees_since(lp_tokens: u64, growth_global: u128, checkpoint: u128) -> Result<u64> {
+        let fees = growth_global
+            .wrapping_sub(checkpoint)
+            .checked_mul(lp_tokens as u128)
+            .ok_or(FluxDexError::Overflow)?
+            / FixedPoint::SCALE;
+        u64::try_from(fees).map_err(|_| FluxDexError::Overflow.into())
+    }
+
+    /// 记录赎回 LP：减少 LP 数量，并按比例缩减初始存入量
+    pub fn record_withdrawal(&mut self, lp_amount: u64) -> Result<()> {
+        if lp_amount > self.lp_tokens {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let remaining = self.lp_tokens - lp_amount;
+        self.initial_deposit_a =
+            Self::scale_down(self.initial_deposit_a, remaining, self.lp_tokens)?;
+        self.initial_deposit_b =
+            Self::scale_down(self.initial_deposit_b, remaining, self.lp_tokens)?;
+        self.lp_tokens = remaining;
+        Ok(())
+    }
+
+    fn scale_down(amount: u64, remaining: u64, total: u64) -> Result<u64> {
+        let scaled = (amount as u128)
+            .checked_mul(remaining as u128)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_div(total as u128)
+            .ok_or(FluxDexError::DivisionByZero)?;
+        Ok(scaled as u64)
+    }
 }
 
 /// 智能流动性管理策略
@@ -73,3 +301,74 @@ pub enum RiskProfile {
     /// 激进：更窄价格区间，更频繁重新平衡
     Aggressive,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{CurveType, MIN_LIQUIDITY_THRESHOLD};
+
+    fn empty_position() -> Position {
+        Position::deserialize(&mut &[0u8; Position::SIZE][..]).unwrap()
+    }
+
+    fn deposit(pool: &mut Pool, position: &mut Position, lp_tokens: u64) {
+        position.accrue_fees(pool).unwrap();
+        position
+            .record_deposit(Pubkey::default(), Pubkey::default(), 0, 0, lp_tokens, 0, 0)
+            .unwrap();
+        pool.lp_supply += lp_tokens;
+    }
+
+    fn withdraw(pool: &mut Pool, position: &mut Position, lp_tokens: u64) {
+        position.accrue_fees(pool).unwrap();
+        position.record_withdrawal(lp_tokens).unwrap();
+        pool.lp_supply -= lp_tokens;
+    }
+
+    fn collect(pool: &mut Pool, position: &mut Position) -> u64 {
+        position.accrue_fees(pool).unwrap();
+        let amount = position.fees_owed_a;
+        position.fees_owed_a = 0;
+        pool.unclaimed_lp_fees_a -= amount;
+        amount
+    }
+
+    #[test]
+    fn test_fee_accrual_across_deposits_withdrawals_and_collects() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 0, 0);
+        let mut alice = empty_position();
+        let mut bob = empty_position();
+
+        // 首次存入锁定 MIN_LIQUIDITY_THRESHOLD，锁定部分不参与手续费分配
+        pool.lp_supply = MIN_LIQUIDITY_THRESHOLD;
+        deposit(&mut pool, &mut alice, 9_000);
+        pool.credit_fee(true, 9_000, 0).unwrap();
+        assert_eq!(pool.unclaimed_lp_fees_a, 9_000);
+
+        // 后加入的 LP 只分享之后产生的手续费
+        deposit(&mut pool, &mut bob, 9_000);
+        pool.credit_fee(true, 1_800, 0).unwrap();
+        assert_eq!(collect(&mut pool, &mut alice), 9_900);
+
+        // 赎回一半后按剩余 LP 分配
+        withdraw(&mut pool, &mut alice, 4_500);
+        pool.credit_fee(true, 1_350, 0).unwrap();
+        assert_eq!(collect(&mut pool, &mut alice), 450);
+        assert_eq!(collect(&mut pool, &mut bob), 1_800);
+
+        // 全部 LP 手续费都被 Position 领走
+        assert_eq!(pool.unclaimed_lp_fees_a, 0);
+        assert_eq!(pool.protocol_fees_a, 0);
+    }
+
+    #[test]
+    fn test_fee_without_claimable_lp_goes_to_protocol() {
+        let mut pool = Pool::for_test(CurveType::ConstantProduct, 0, 0);
+        pool.lp_supply = MIN_LIQUIDITY_THRESHOLD;
+
+        pool.credit_fee(false, 500, 1000).unwrap();
+        assert_eq!(pool.protocol_fees_b, 500);
+        assert_eq!(pool.unclaimed_lp_fees_b, 0);
+        assert_eq!(pool.fee_growth_global_b, 0);
+    }
+}
@@ -2,3 +2,10 @@ pub const POOL_SEED: &[u8] = b"pool";
 pub const LP_MINT_SEED: &[u8] = b"lp_mint";
 pub const TOKEN_A_VAULT_SEED: &[u8] = b"token_vault_a";
 pub const TOKEN_B_VAULT_SEED: &[u8] = b"token_vault_b";
+pub const POSITION_SEED: &[u8] = b"position";
+pub const GLOBAL_CONFIG_SEED: &[u8] = b"global_config";
+pub const LOCKED_LP_SEED: &[u8] = b"locked_lp";
+pub const LOCKED_LP_AUTHORITY_SEED: &[u8] = b"locked_lp_authority";
+pub const OBSERVATION_SEED: &[u8] = b"observation";
+pub const TICK_ARRAY_SEED: &[u8] = b"tick_array";
+pub const METADATA_SEED: &[u8] = b"metadata";
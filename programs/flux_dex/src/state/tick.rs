@@ -0,0 +1,483 @@
+use crate::error::FluxDexError;
+use crate::state::{CurveType, Pool, MAX_TICK, MIN_TICK, TICK_ARRAY_SIZE};
+use crate::utils::{ConcentratedMath, FixedPoint, MathUtils};
+use anchor_lang::prelude::*;
+use std::ops::DerefMut;
+
+/// 单个 tick 的流动性与手续费状态
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tick {
+    pub initialized: bool,
+    pub liquidity_net: i128,        // 价格自下而上穿过时活跃流动性的变化
+    pub liquidity_gross: u128,      // 以该 tick 为边界的流动性总量
+    pub fee_growth_outside_a: u128, // tick 另一侧的手续费增长 A
+    pub fee_growth_outside_b: u128, // tick 另一侧的手续费增长 B
+}
+
+impl Tick {
+    pub const SIZE: usize = 1 + 16 + 16 + 16 + 16;
+
+    /// 更新以该 tick 为边界的流动性
+    ///
+    /// 首次初始化时约定 tick 以下的手续费增长全部发生在初始化之前
+    pub fn update(
+        &mut self,
+        tick_index: i32,
+        current_tick: i32,
+        liquidity_delta: i128,
+        fee_growth_global_a: u128,
+        fee_growth_global_b: u128,
+        is_upper: bool,
+    ) -> Result<()> {
+        let liquidity_gross = add_liquidity_delta(self.liquidity_gross, liquidity_delta)?;
+
+        if self.liquidity_gross == 0 && liquidity_gross > 0 {
+            if tick_index <= current_tick {
+                self.fee_growth_outside_a = fee_growth_global_a;
+                self.fee_growth_outside_b = fee_growth_global_b;
+            }
+            self.initialized = true;
+        }
+
+        self.liquidity_net = if is_upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        }
+        .ok_or(FluxDexError::Overflow)?;
+        self.liquidity_gross = liquidity_gross;
+        Ok(())
+    }
+
+    /// 价格穿过 tick：翻转 outside 手续费增长，返回 liquidity_net
+    pub fn cross(&mut self, fee_growth_global_a: u128, fee_growth_global_b: u128) -> i128 {
+        self.fee_growth_outside_a = fee_growth_global_a.wrapping_sub(self.fee_growth_outside_a);
+        self.fee_growth_outside_b = fee_growth_global_b.wrapping_sub(self.fee_growth_outside_b);
+        self.liquidity_net
+    }
+}
+
+/// 连续 TICK_ARRAY_SIZE 个可用 tick 的状态
+#[account]
+pub struct TickArray {
+    pub pool: Pubkey,
+    pub start_tick_index: i32,
+    pub ticks: Vec<Tick>,
+    pub bump: u8,
+}
+
+impl TickArray {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // pool
+        4 + // start_tick_index
+        4 + TICK_ARRAY_SIZE * Tick::SIZE + // ticks
+        1; // bump
+
+    /// 每个 TickArray 覆盖的 tick 跨度
+    pub fn span(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE as i32 * tick_spacing as i32
+    }
+
+    /// 包含 tick 的 TickArray 起始 index
+    pub fn start_index_for(tick_index: i32, tick_spacing: u16) -> i32 {
+        let span = Self::span(tick_spacing);
+        tick_index.div_euclid(span) * span
+    }
+
+    pub fn contains(&self, tick_index: i32, tick_spacing: u16) -> bool {
+        tick_index >= self.start_tick_index
+            && tick_index < self.start_tick_index + Self::span(tick_spacing)
+    }
+
+    pub fn tick(&self, tick_index: i32, tick_spacing: u16) -> Result<&Tick> {
+        let offset = self.offset(tick_index, tick_spacing)?;
+        Ok(&self.ticks[offset])
+    }
+
+    pub fn tick_mut(&mut self, tick_index: i32, tick_spacing: u16) -> Result<&mut Tick> {
+        let offset = self.offset(tick_index, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+
+    /// 流动性归零后清空 tick
+    pub fn clear_if_unused(&mut self, tick_index: i32, tick_spacing: u16) -> Result<()> {
+        let tick = self.tick_mut(tick_index, tick_spacing)?;
+        if tick.liquidity_gross == 0 {
+            *tick = Tick::default();
+        }
+        Ok(())
+    }
+
+    /// 在本数组内查找下一个目标 tick，返回 (tick_index, 是否已初始化)
+    ///
+    /// a_to_b 时返回 <= current_tick 的最大已初始化 tick，找不到则返回数组起点；
+    /// 否则返回 > current_tick 的最小已初始化 tick，找不到则返回数组末尾。
+    /// 数组内没有可前进的 tick 时返回 None
+    pub fn next_tick(
+        &self,
+        current_tick: i32,
+        tick_spacing: u16,
+        a_to_b: bool,
+    ) -> Option<(i32, bool)> {
+        let spacing = tick_spacing as i32;
+        let tick_at = |offset: usize| self.start_tick_index + offset as i32 * spacing;
+
+        if a_to_b {
+            if current_tick < self.start_tick_index {
+                return None;
+            }
+            let last = ((current_tick - self.start_tick_index) / spacing)
+                .min(TICK_ARRAY_SIZE as i32 - 1) as usize;
+            let found = (0..=last).rev().find(|&i| self.ticks[i].initialized);
+            Some(match found {
+                Some(i) => (tick_at(i), true),
+                None => (self.start_tick_index, false),
+            })
+        } else {
+            let first = if current_tick < self.start_tick_index {
+                0
+            } else {
+                ((current_tick - self.start_tick_index) / spacing + 1) as usize
+            };
+            if first >= TICK_ARRAY_SIZE {
+                return None;
+            }
+            let found = (first..TICK_ARRAY_SIZE).find(|&i| self.ticks[i].initialized);
+            Some(match found {
+                Some(i) => (tick_at(i), true),
+                None => (tick_at(TICK_ARRAY_SIZE - 1), false),
+            })
+        }
+    }
+
+    fn offset(&self, tick_index: i32, tick_spacing: u16) -> Result<usize> {
+        if !self.contains(tick_index, tick_spacing) {
+            return Err(FluxDexError::TickOutOfRange.into());
+        }
+        if tick_index % tick_spacing as i32 != 0 {
+            return Err(FluxDexError::InvalidTickSpacing.into());
+        }
+        Ok(((tick_index - self.start_tick_index) / tick_spacing as i32) as usize)
+    }
+}
+
+/// 集中流动性兑换结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConcentratedSwapResult {
+    pub amount_in: u64, // 含手续费
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub protocol_fee: u64,
+    pub sqrt_price_x64: u128,
+    pub current_tick: i32,
+    pub liquidity: u128,
+    pub fee_growth_global_in: u128, // 输入代币一侧的手续费增长
+}
+
+impl Pool {
+    /// 校验头寸区间的 tick
+    pub fn validate_tick_range(&self, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        if self.curve_type != CurveType::Concentrated {
+            return Err(FluxDexError::InvalidCurveType.into());
+        }
+        if tick_lower >= tick_upper {
+            return Err(FluxDexError::InvalidPriceRange.into());
+        }
+        if tick_lower < MIN_TICK || tick_upper > MAX_TICK {
+            return Err(FluxDexError::TickOutOfRange.into());
+        }
+        let spacing = self.tick_spacing as i32;
+        if tick_lower % spacing != 0 || tick_upper % spacing != 0 {
+            return Err(FluxDexError::InvalidTickSpacing.into());
+        }
+        Ok(())
+    }
+
+    /// 更新边界 tick 的流动性
+    pub fn update_tick(
+        &self,
+        tick_array: &mut TickArray,
+        tick_index: i32,
+        liquidity_delta: i128,
+        is_upper: bool,
+    ) -> Result<()> {
+        tick_array.tick_mut(tick_index, self.tick_spacing)?.update(
+            tick_index,
+            self.current_tick,
+            liquidity_delta,
+            self.fee_growth_global_a,
+            self.fee_growth_global_b,
+            is_upper,
+        )
+    }
+
+    /// 区间 [tick_lower, tick_upper) 内每单位流动性累计的手续费
+    pub fn fee_growth_inside(
+        &self,
+        tick_lower: i32,
+        lower: &Tick,
+        tick_upper: i32,
+        upper: &Tick,
+    ) -> (u128, u128) {
+        let inside = |global: u128, lower_outside: u128, upper_outside: u128| {
+            let below = if self.current_tick >= tick_lower {
+                lower_outside
+            } else {
+                global.wrapping_sub(lower_outside)
+            };
+            let above = if self.current_tick < tick_upper {
+                upper_outside
+            } else {
+                global.wrapping_sub(upper_outside)
+            };
+            global.wrapping_sub(below).wrapping_sub(above)
+        };
+
+        (
+            inside(
+                self.fee_growth_global_a,
+                lower.fee_growth_outside_a,
+                upper.fee_growth_outside_a,
+            ),
+            inside(
+                self.fee_growth_global_b,
+                lower.fee_growth_outside_b,
+                upper.fee_growth_outside_b,
+            ),
+        )
+    }
+
+    /// 沿 tick_arrays 执行集中流动性兑换，穿过 tick 时更新 tick 状态
+    ///
+    /// tick_arrays 须已按兑换方向排好且首个数组包含当前 tick；池子状态由调用方写回
+    pub fn compute_concentrated_swap<T: DerefMut<Target = TickArray>>(
+        &self,
+        tick_arrays: &mut [T],
+        amount: u64,
+        exact_in: bool,
+        a_to_b: bool,
+        fee_bps: u16,
+        protocol_fee_bps: u16,
+    ) -> Result<ConcentratedSwapResult> {
+        if self.curve_type != CurveType::Concentrated {
+            return Err(FluxDexError::InvalidCurveType.into());
+        }
+        if amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+
+        let spacing = self.tick_spacing;
+        let mut sqrt_price_x64 = self.sqrt_price_x64;
+        let mut current_tick = self.current_tick;
+        let mut liquidity = self.liquidity;
+        let (mut fee_growth_in, fee_growth_other) = if a_to_b {
+            (self.fee_growth_global_a, self.fee_growth_global_b)
+        } else {
+            (self.fee_growth_global_b, self.fee_growth_global_a)
+        };
+
+        let mut amount_remaining = amount as u128;
+        let mut amount_calculated = 0u128;
+        let mut fee_amount = 0u128;
+        let mut protocol_fee = 0u128;
+        let mut array_index = 0;
+
+        while amount_remaining > 0 {
+            let tick_array = tick_arrays
+                .get_mut(array_index)
+                .ok_or(FluxDexError::InsufficientLiquidity)?;
+            let Some((target_tick, initialized)) =
+                tick_array.next_tick(current_tick, spacing, a_to_b)
+            else {
+                array_index += 1;
+                continue;
+            };
+            let target_tick = target_tick.clamp(MIN_TICK, MAX_TICK);
+            let sqrt_price_target_x64 = ConcentratedMath::sqrt_price_at_tick(target_tick)?;
+
+            let step = ConcentratedMath::compute_swap_step(
+                sqrt_price_x64,
+                sqrt_price_target_x64,
+                liquidity,
+                amount_remaining,
+                fee_bps,
+                exact_in,
+            )?;
+
+            if exact_in {
+                amount_remaining = amount_remaining
+                    .checked_sub(step.amount_in + step.fee_amount)
+                    .ok_or(FluxDexError::Underflow)?;
+                amount_calculated += step.amount_out;
+            } else {
+                amount_remaining = amount_remaining
+                    .checked_sub(step.amount_out)
+                    .ok_or(FluxDexError::Underflow)?;
+                amount_calculated += step.amount_in + step.fee_amount;
+            }
+
+            // 协议费之外的手续费按当前活跃流动性分配
+            let step_protocol_fee = step.fee_amount * protocol_fee_bps as u128 / 10000;
+            if liquidity > 0 {
+                let lp_fee = step.fee_amount - step_protocol_fee;
+                fee_growth_in = fee_growth_in.wrapping_add(MathUtils::mul_div(
+                    lp_fee,
+                    FixedPoint::SCALE,
+                    liquidity,
+                )?);
+            }
+            fee_amount += step.fee_amount;
+            protocol_fee += step_protocol_fee;
+
+            sqrt_price_x64 = step.sqrt_price_next_x64;
+            if sqrt_price_x64 == sqrt_price_target_x64 {
+                if initialized {
+                    let (growth_a, growth_b) = if a_to_b {
+                        (fee_growth_in, fee_growth_other)
+                    } else {
+                        (fee_growth_other, fee_growth_in)
+                    };
+                    let liquidity_net = tick_array
+                        .tick_mut(target_tick, spacing)?
+                        .cross(growth_a, growth_b);
+                    let liquidity_delta = if a_to_b {
+                        liquidity_net.checked_neg().ok_or(FluxDexError::Overflow)?
+                    } else {
+                        liquidity_net
+                    };
+                    liquidity = add_liquidity_delta(liquidity, liquidity_delta)?;
+                }
+                current_tick = if a_to_b { target_tick - 1 } else { target_tick };
+
+                let at_bound = if a_to_b {
+                    target_tick <= MIN_TICK
+                } else {
+                    target_tick >= MAX_TICK
+                };
+                if at_bound && amount_remaining > 0 {
+                    return Err(FluxDexError::InsufficientLiquidity.into());
+                }
+            } else {
+                current_tick = ConcentratedMath::tick_at_sqrt_price(sqrt_price_x64)?;
+            }
+        }
+
+        let (amount_in, amount_out) = if exact_in {
+            (amount as u128, amount_calculated)
+        } else {
+            (amount_calculated, amount as u128)
+        };
+
+        Ok(ConcentratedSwapResult {
+            amount_in: u64::try_from(amount_in).map_err(|_| FluxDexError::Overflow)?,
+            amount_out: u64::try_from(amount_out).map_err(|_| FluxDexError::Overflow)?,
+            fee_amount: u64::try_from(fee_amount).map_err(|_| FluxDexError::Overflow)?,
+            protocol_fee: u64::try_from(protocol_fee).map_err(|_| FluxDexError::Overflow)?,
+            sqrt_price_x64,
+            current_tick,
+            liquidity,
+            fee_growth_global_in: fee_growth_in,
+        })
+    }
+}
+
+/// 流动性加上有符号的变化量
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    if delta >= 0 {
+        liquidity
+            .checked_add(delta as u128)
+            .ok_or(FluxDexError::Overflow.into())
+    } else {
+        liquidity
+            .checked_sub(delta.unsigned_abs())
+            .ok_or(FluxDexError::InsufficientLiquidity.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACING: u16 = 10;
+
+    fn concentrated_pool() -> Pool {
+        let mut pool = Pool::for_test(CurveType::Concentrated, 0, 0);
+        pool.tick_spacing = SPACING;
+        pool.sqrt_price_x64 = ConcentratedMath::sqrt_price_at_tick(0).unwrap();
+        pool.current_tick = 0;
+        pool
+    }
+
+    fn empty_tick_array() -> TickArray {
+        TickArray {
+            pool: Pubkey::default(),
+            start_tick_index: 0,
+            ticks: vec![Tick::default(); TICK_ARRAY_SIZE],
+            bump: 0,
+        }
+    }
+
+    fn open_range(pool: &mut Pool, array: &mut TickArray, lower: i32, upper: i32, liquidity: u128) {
+        pool.update_tick(array, lower, liquidity as i128, false)
+            .unwrap();
+        pool.update_tick(array, upper, liquidity as i128, true)
+            .unwrap();
+        if lower <= pool.current_tick && pool.current_tick < upper {
+            pool.liquidity += liquidity;
+        }
+    }
+
+    fn inside_b(pool: &Pool, array: &TickArray, lower: i32, upper: i32) -> u128 {
+        let lower_tick = array.tick(lower, SPACING).unwrap();
+        let upper_tick = array.tick(upper, SPACING).unwrap();
+        pool.fee_growth_inside(lower, lower_tick, upper, upper_tick)
+            .1
+    }
+
+    #[test]
+    fn test_swap_crosses_initialized_tick_and_splits_fees_by_range() {
+        const WIDE: u128 = 1_000_000_000;
+        const NARROW: u128 = 1_000_000_000;
+
+        let mut pool = concentrated_pool();
+        let mut array = empty_tick_array();
+        open_range(&mut pool, &mut array, 0, 200, WIDE);
+        open_range(&mut pool, &mut array, 50, 200, NARROW);
+        open_range(&mut pool, &mut array, 250, 300, NARROW);
+        assert_eq!(pool.liquidity, WIDE);
+
+        // B -> A 推高价格，穿过 tick 50 后 NARROW 区间加入活跃流动性
+        let result = pool
+            .compute_concentrated_swap(&mut [&mut array], 5_000_000, true, false, 30, 0)
+            .unwrap();
+        assert!(result.current_tick >= 50 && result.current_tick < 200);
+        assert_eq!(result.liquidity, WIDE + NARROW);
+
+        // 穿过时 tick 50 的 outside 翻转为穿过前的累计增长
+        let growth_at_cross = array.tick(50, SPACING).unwrap().fee_growth_outside_b;
+        assert!(growth_at_cross > 0);
+        assert!(growth_at_cross < result.fee_growth_global_in);
+        assert_eq!(array.tick(50, SPACING).unwrap().fee_growth_outside_a, 0);
+
+        pool.current_tick = result.current_tick;
+        pool.sqrt_price_x64 = result.sqrt_price_x64;
+        pool.liquidity = result.liquidity;
+        pool.fee_growth_global_b = result.fee_growth_global_in;
+
+        // 区间内：WIDE 获得全部增长，NARROW 只获得进入区间之后的增长
+        let wide_inside = inside_b(&pool, &array, 0, 200);
+        let narrow_inside = inside_b(&pool, &array, 50, 200);
+        assert_eq!(wide_inside, result.fee_growth_global_in);
+        assert_eq!(narrow_inside, result.fee_growth_global_in - growth_at_cross);
+
+        // 区间外：价格从未到达，没有手续费增长
+        assert_eq!(inside_b(&pool, &array, 250, 300), 0);
+
+        // 按区间增长分给两个头寸的手续费之和不超过实际收取的手续费
+        let distributed = MathUtils::mul_div(wide_inside, WIDE, FixedPoint::SCALE).unwrap()
+            + MathUtils::mul_div(narrow_inside, NARROW, FixedPoint::SCALE).unwrap();
+        let fee_amount = result.fee_amount as u128;
+        assert!(distributed <= fee_amount);
+        assert!(fee_amount - distributed <= 2);
+    }
+}
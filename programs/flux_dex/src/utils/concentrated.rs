@@ -0,0 +1,439 @@
+// programs/flux_dex/src/utils/concentrated.rs
+use crate::error::FluxDexError;
+use crate::state::{MAX_TICK, MIN_TICK};
+use crate::utils::{FixedPoint, MathUtils};
+use anchor_lang::prelude::*;
+
+/// 单步兑换结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwapStep {
+    pub sqrt_price_next_x64: u128,
+    pub amount_in: u128, // 不含手续费
+    pub amount_out: u128,
+    pub fee_amount: u128,
+}
+
+/// 集中流动性数学：价格 P = 1.0001^tick（以 Token B 计价的 Token A），
+/// 平方根价格以 Q64.64 定点数保存
+pub struct ConcentratedMath;
+
+impl ConcentratedMath {
+    /// Q64.64 中的 1.0
+    pub const Q64: u128 = 1 << 64;
+
+    /// ln(1.0001) / 2，SCALE 精度
+    const HALF_LN_TICK_BASE: u128 = 49_997_500_166_654;
+
+    /// tick 对应的平方根价格 sqrt(1.0001^tick)，Q64.64
+    pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+        if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+            return Err(FluxDexError::TickOutOfRange.into());
+        }
+
+        // 负 tick 取正 tick 的倒数，保证两侧精度一致且单调
+        let exponent = tick.unsigned_abs() as i128 * Self::HALF_LN_TICK_BASE as i128;
+        let ratio = FixedPoint::exp(exponent)?.value;
+        let sqrt_price = MathUtils::mul_div(ratio, Self::Q64, FixedPoint::SCALE)?;
+
+        if tick >= 0 {
+            Ok(sqrt_price)
+        } else {
+            Ok(u128::MAX / sqrt_price)
+        }
+    }
+
+    /// 平方根价格所在的 tick：满足 sqrt_price_at_tick(tick) <= sqrt_price 的最大 tick
+    pub fn tick_at_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
+        if sqrt_price_x64 < Self::min_sqrt_price()? || sqrt_price_x64 >= Self::max_sqrt_price()? {
+            return Err(FluxDexError::InvalidSqrtPrice.into());
+        }
+
+        // 先用对数估算，再用 sqrt_price_at_tick 校正舍入误差
+        let ratio = MathUtils::mul_div(sqrt_price_x64, FixedPoint::SCALE, Self::Q64)?;
+        let ln = FixedPoint::new(ratio).ln()?;
+        let mut tick = ln.div_euclid(Self::HALF_LN_TICK_BASE as i128) as i32;
+        tick = tick.clamp(MIN_TICK, MAX_TICK - 1);
+
+        while tick > MIN_TICK && Self::sqrt_price_at_tick(tick)? > sqrt_price_x64 {
+            tick -= 1;
+        }
+        while tick < MAX_TICK - 1 && Self::sqrt_price_at_tick(tick + 1)? <= sqrt_price_x64 {
+            tick += 1;
+        }
+        Ok(tick)
+    }
+
+    pub fn min_sqrt_price() -> Result<u128> {
+        Self::sqrt_price_at_tick(MIN_TICK)
+    }
+
+    pub fn max_sqrt_price() -> Result<u128> {
+        Self::sqrt_price_at_tick(MAX_TICK)
+    }
+
+    /// Q64.64 平方根价格转换为 FixedPoint 价格
+    pub fn sqrt_price_to_price(sqrt_price_x64: u128) -> Result<FixedPoint> {
+        let price_x64 = MathUtils::mul_div(sqrt_price_x64, sqrt_price_x64, Self::Q64)?;
+        Ok(FixedPoint::new(MathUtils::mul_div(
+            price_x64,
+            FixedPoint::SCALE,
+            Self::Q64,
+        )?))
+    }
+
+    /// 价格区间内 Token A 的数量：L · (√Pu - √Pl) / (√Pu · √Pl)
+    pub fn get_amount_a_delta(
+        sqrt_price_a_x64: u128,
+        sqrt_price_b_x64: u128,
+        liquidity: u128,
+        round_up: bool,
+    ) -> Result<u128> {
+        let (lower, upper) = Self::sorted(sqrt_price_a_x64, sqrt_price_b_x64);
+        if lower == 0 {
+            return Err(FluxDexError::InvalidSqrtPrice.into());
+        }
+
+        if round_up {
+            let scaled = MathUtils::mul_div_ceil(liquidity, upper - lower, upper)?;
+            MathUtils::mul_div_ceil(scaled, Self::Q64, lower)
+        } else {
+            let scaled = MathUtils::mul_div(liquidity, upper - lower, upper)?;
+            MathUtils::mul_div(scaled, Self::Q64, lower)
+        }
+    }
+
+    /// 价格区间内 Token B 的数量：L · (√Pu - √Pl)
+    pub fn get_amount_b_delta(
+        sqrt_price_a_x64: u128,
+        sqrt_price_b_x64: u128,
+        liquidity: u128,
+        round_up: bool,
+    ) -> Result<u128> {
+        let (lower, upper) = Self::sorted(sqrt_price_a_x64, sqrt_price_b_x64);
+        if round_up {
+            MathUtils::mul_div_ceil(liquidity, upper - lower, Self::Q64)
+        } else {
+            MathUtils::mul_div(liquidity, upper - lower, Self::Q64)
+        }
+    }
+
+    /// 给定输入量后的平方根价格，舍入方向保证池子不亏
+    pub fn next_sqrt_price_from_input(
+        sqrt_price_x64: u128,
+        liquidity: u128,
+        amount_in: u128,
+        a_to_b: bool,
+    ) -> Result<u128> {
+        if liquidity == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+        if amount_in == 0 {
+            return Ok(sqrt_price_x64);
+        }
+
+        if a_to_b {
+            // √P' = L · √P / (L + Δx · √P)，向上取整
+            let product = MathUtils::mul_div(amount_in, sqrt_price_x64, Self::Q64)?;
+            let denominator = liquidity
+                .checked_add(product)
+                .ok_or(FluxDexError::Overflow)?;
+            MathUtils::mul_div_ceil(liquidity, sqrt_price_x64, denominator)
+        } else {
+            // √P' = √P + Δy / L，向下取整
+            let delta = MathUtils::mul_div(amount_in, Self::Q64, liquidity)?;
+            sqrt_price_x64
+                .checked_add(delta)
+                .ok_or(FluxDexError::Overflow.into())
+        }
+    }
+
+    /// 给定输出量后的平方根价格，舍入方向保证池子不亏
+    pub fn next_sqrt_price_from_output(
+        sqrt_price_x64: u128,
+        liquidity: u128,
+        amount_out: u128,
+        a_to_b: bool,
+    ) -> Result<u128> {
+        if liquidity == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+        if amount_out == 0 {
+            return Ok(sqrt_price_x64);
+        }
+
+        if a_to_b {
+            // 输出 Token B：√P' = √P - Δy / L，向下取整
+            let delta = MathUtils::mul_div_ceil(amount_out, Self::Q64, liquidity)?;
+            sqrt_price_x64
+                .checked_sub(delta)
+                .ok_or(FluxDexError::InsufficientLiquidity.into())
+        } else {
+            // 输出 Token A：√P' = L · √P / (L - Δx · √P)，向上取整
+            let product = MathUtils::mul_div_ceil(amount_out, sqrt_price_x64, Self::Q64)?;
+            let denominator = liquidity
+                .checked_sub(product)
+                .filter(|d| *d > 0)
+                .ok_or(FluxDexError::InsufficientLiquidity)?;
+            MathUtils::mul_div_ceil(liquidity, sqrt_price_x64, denominator)
+        }
+    }
+
+    /// 在一个流动性不变的区间内兑换，直到用完剩余数量或到达目标价格
+    ///
+    /// exact_in 时 amount_remaining 含手续费；否则为剩余的目标输出量
+    pub fn compute_swap_step(
+        sqrt_price_current_x64: u128,
+        sqrt_price_target_x64: u128,
+        liquidity: u128,
+        amount_remaining: u128,
+        fee_bps: u16,
+        exact_in: bool,
+    ) -> Result<SwapStep> {
+        let a_to_b = sqrt_price_current_x64 >= sqrt_price_target_x64;
+        let fee_factor = 10000u128
+            .checked_sub(fee_bps as u128)
+            .ok_or(FluxDexError::InvalidCalculation)?;
+
+        let mut amount_in = 0;
+        let mut amount_out = 0;
+        let sqrt_price_next_x64;
+
+        if exact_in {
+            let amount_remaining_less_fee = amount_remaining * fee_factor / 10000;
+            amount_in = Self::amount_in_between(
+                sqrt_price_current_x64,
+                sqrt_price_target_x64,
+                liquidity,
+                a_to_b,
+            )?;
+            sqrt_price_next_x64 = if amount_remaining_less_fee >= amount_in {
+                sqrt_price_target_x64
+            } else {
+                Self::next_sqrt_price_from_input(
+                    sqrt_price_current_x64,
+                    liquidity,
+                    amount_remaining_less_fee,
+                    a_to_b,
+                )?
+            };
+        } else {
+            amount_out = Self::amount_out_between(
+                sqrt_price_current_x64,
+                sqrt_price_target_x64,
+                liquidity,
+                a_to_b,
+            )?;
+            sqrt_price_next_x64 = if amount_remaining >= amount_out {
+                sqrt_price_target_x64
+            } else {
+                Self::next_sqrt_price_from_output(
+                    sqrt_price_current_x64,
+                    liquidity,
+                    amount_remaining,
+                    a_to_b,
+                )?
+            };
+        }
+
+        let reached_target = sqrt_price_next_x64 == sqrt_price_target_x64;
+        if !(reached_target && exact_in) {
+            amount_in = Self::amount_in_between(
+                sqrt_price_current_x64,
+                sqrt_price_next_x64,
+                liquidity,
+                a_to_b,
+            )?;
+        }
+        if !reached_target || exact_in {
+            amount_out = Self::amount_out_between(
+                sqrt_price_current_x64,
+                sqrt_price_next_x64,
+                liquidity,
+                a_to_b,
+            )?;
+        }
+
+        if !exact_in && amount_out > amount_remaining {
+            amount_out = amount_remaining;
+        }
+
+        let fee_amount = if exact_in && !reached_target {
+            // 未到达目标价格时，剩余输入全部计为手续费
+            amount_remaining - amount_in
+        } else {
+            MathUtils::mul_div_ceil(amount_in, fee_bps as u128, fee_factor)?
+        };
+
+        Ok(SwapStep {
+            sqrt_price_next_x64,
+            amount_in,
+            amount_out,
+            fee_amount,
+        })
+    }
+
+    /// 在 tick 区间 [lower, upper) 内提供 liquidity 所需（或可取回）的代币数量
+    pub fn amounts_for_liquidity(
+        current_tick: i32,
+        sqrt_price_x64: u128,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity: u128,
+        round_up: bool,
+    ) -> Result<(u128, u128)> {
+        let sqrt_lower = Self::sqrt_price_at_tick(tick_lower)?;
+        let sqrt_upper = Self::sqrt_price_at_tick(tick_upper)?;
+
+        if current_tick < tick_lower {
+            // 价格低于区间：全部为 Token A
+            let amount_a = Self::get_amount_a_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?;
+            Ok((amount_a, 0))
+        } else if current_tick >= tick_upper {
+            // 价格高于区间：全部为 Token B
+            let amount_b = Self::get_amount_b_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?;
+            Ok((0, amount_b))
+        } else {
+            let amount_a =
+                Self::get_amount_a_delta(sqrt_price_x64, sqrt_upper, liquidity, round_up)?;
+            let amount_b =
+                Self::get_amount_b_delta(sqrt_lower, sqrt_price_x64, liquidity, round_up)?;
+            Ok((amount_a, amount_b))
+        }
+    }
+
+    fn amount_in_between(current: u128, next: u128, liquidity: u128, a_to_b: bool) -> Result<u128> {
+        if a_to_b {
+            Self::get_amount_a_delta(next, current, liquidity, true)
+        } else {
+            Self::get_amount_b_delta(current, next, liquidity, true)
+        }
+    }
+
+    fn amount_out_between(
+        current: u128,
+        next: u128,
+        liquidity: u128,
+        a_to_b: bool,
+    ) -> Result<u128> {
+        if a_to_b {
+            Self::get_amount_b_delta(next, current, liquidity, false)
+        } else {
+            Self::get_amount_a_delta(current, next, liquidity, false)
+        }
+    }
+
+    fn sorted(a: u128, b: u128) -> (u128, u128) {
+        if a <= b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_price_at_tick() {
+        assert_eq!(
+            ConcentratedMath::sqrt_price_at_tick(0).unwrap(),
+            ConcentratedMath::Q64
+        );
+
+        // 1.0001^(10000/2) ≈ 1.6487
+        let sqrt_price = ConcentratedMath::sqrt_price_at_tick(10000).unwrap();
+        let expected = 1.648_680_055_931_175_f64 * ConcentratedMath::Q64 as f64;
+        assert!((sqrt_price as f64 - expected).abs() / expected < 1e-12);
+
+        // 正负 tick 互为倒数
+        let inverse = ConcentratedMath::sqrt_price_at_tick(-10000).unwrap();
+        let product = MathUtils::mul_div(sqrt_price, inverse, ConcentratedMath::Q64).unwrap();
+        assert!(product.abs_diff(ConcentratedMath::Q64) < 1_000);
+
+        assert!(ConcentratedMath::sqrt_price_at_tick(MAX_TICK + 1).is_err());
+        assert!(ConcentratedMath::sqrt_price_at_tick(MIN_TICK).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_price_round_trip() {
+        for tick in [MIN_TICK, -100_000, -1, 0, 1, 64, 12_345, MAX_TICK - 1] {
+            let sqrt_price = ConcentratedMath::sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(
+                ConcentratedMath::tick_at_sqrt_price(sqrt_price).unwrap(),
+                tick
+            );
+            if tick > MIN_TICK {
+                assert_eq!(
+                    ConcentratedMath::tick_at_sqrt_price(sqrt_price - 1).unwrap(),
+                    tick - 1
+                );
+            }
+        }
+        assert!(
+            ConcentratedMath::tick_at_sqrt_price(ConcentratedMath::max_sqrt_price().unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_amounts_for_liquidity() {
+        let liquidity = 1_000_000_000_000u128;
+        let sqrt_price = ConcentratedMath::sqrt_price_at_tick(0).unwrap();
+
+        // 价格位于区间中央时两种代币数量接近
+        let (amount_a, amount_b) =
+            ConcentratedMath::amounts_for_liquidity(0, sqrt_price, -100, 100, liquidity, true)
+                .unwrap();
+        assert!(amount_a.abs_diff(amount_b) <= 1);
+
+        // 区间在价格之上只需要 Token A
+        let (amount_a, amount_b) =
+            ConcentratedMath::amounts_for_liquidity(0, sqrt_price, 100, 200, liquidity, true)
+                .unwrap();
+        assert!(amount_a > 0);
+        assert_eq!(amount_b, 0);
+    }
+
+    #[test]
+    fn test_swap_step_exact_in_and_out() {
+        let liquidity = 1_000_000_000_000u128;
+        let current = ConcentratedMath::sqrt_price_at_tick(0).unwrap();
+        let target = ConcentratedMath::sqrt_price_at_tick(-1000).unwrap();
+
+        // 区间内部分成交
+        let step =
+            ConcentratedMath::compute_swap_step(current, target, liquidity, 1_000_000, 30, true)
+                .unwrap();
+        assert!(step.sqrt_price_next_x64 < current && step.sqrt_price_next_x64 > target);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000_000);
+        assert!(step.amount_out < step.amount_in);
+
+        // 反向：精确输出所需输入不少于精确输入
+        let reverse = ConcentratedMath::compute_swap_step(
+            current,
+            target,
+            liquidity,
+            step.amount_out,
+            30,
+            false,
+        )
+        .unwrap();
+        assert_eq!(reverse.amount_out, step.amount_out);
+        // 两个方向只差舍入误差
+        assert!((reverse.amount_in + reverse.fee_amount).abs_diff(1_000_000) <= 2);
+
+        // 输入充足时到达目标价格
+        let step = ConcentratedMath::compute_swap_step(
+            current,
+            target,
+            liquidity,
+            u64::MAX as u128,
+            30,
+            true,
+        )
+        .unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+    }
+}
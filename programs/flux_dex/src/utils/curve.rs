@@ -0,0 +1,354 @@
+// programs/flux_dex/src/utils/curve.rs
+use crate::utils::{FixedPoint, MathUtils, StableSwapMath, WeightedMath};
+use crate::FluxDexError;
+use anchor_lang::prelude::*;
+
+/// 基于储备量定价的兑换曲线
+///
+/// 储备量按 (Token A, Token B) 传入，a_to_b 指定兑换方向。
+/// 新曲线只需实现兑换与首次铸造，LP 存取默认按储备比例计算
+pub trait SwapCurve {
+    /// 给定输入量计算输出量，输入中含 fee_bps 的手续费
+    fn swap_output(
+        &self,
+        amount_in: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64>;
+
+    /// 获得指定输出量所需的输入量（含手续费），向上取整
+    fn swap_input(
+        &self,
+        amount_out: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64>;
+
+    /// 首次存入铸造的 LP 数量，即曲线在初始储备下的不变量
+    fn initial_lp_tokens(&self, deposit_a: u64, deposit_b: u64) -> Result<u64>;
+
+    /// 存入应铸造的 LP 数量，之后的存入按比例铸造
+    fn deposit_lp_tokens(
+        &self,
+        deposit_a: u64,
+        deposit_b: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        lp_supply: u64,
+    ) -> Result<u64> {
+        if lp_supply == 0 {
+            return self.initial_lp_tokens(deposit_a, deposit_b);
+        }
+        MathUtils::calculate_lp_tokens(deposit_a, deposit_b, reserve_a, reserve_b, lp_supply)
+    }
+
+    /// 赎回 lp_tokens 可取回的 (Token A, Token B) 数量
+    fn withdraw_amounts(
+        &self,
+        lp_tokens: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        lp_supply: u64,
+    ) -> Result<(u64, u64)> {
+        MathUtils::calculate_remove_amounts(lp_tokens, lp_supply, reserve_a, reserve_b)
+    }
+
+    /// 现价：以输出代币计价的输入代币价格
+    fn spot_price(&self, reserve_a: u64, reserve_b: u64, a_to_b: bool) -> FixedPoint {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        weighted_spot_price(reserve_in, reserve_out, 5000, 5000)
+    }
+}
+
+/// 恒定乘积 (x * y = k)
+pub struct ConstantProductCurve;
+
+impl SwapCurve for ConstantProductCurve {
+    fn swap_output(
+        &self,
+        amount_in: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        MathUtils::calculate_amm_output(amount_in, reserve_in, reserve_out, fee_bps)
+    }
+
+    fn swap_input(
+        &self,
+        amount_out: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        MathUtils::calculate_amm_input(amount_out, reserve_in, reserve_out, fee_bps)
+    }
+
+    fn initial_lp_tokens(&self, deposit_a: u64, deposit_b: u64) -> Result<u64> {
+        // 几何平均
+        MathUtils::calculate_lp_tokens(deposit_a, deposit_b, 0, 0, 0)
+    }
+}
+
+/// Curve StableSwap，amp 为当前时刻的放大系数
+pub struct StableSwapCurve {
+    pub amp: u64,
+}
+
+impl SwapCurve for StableSwapCurve {
+    fn swap_output(
+        &self,
+        amount_in: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        StableSwapMath::calculate_swap_output(amount_in, reserve_in, reserve_out, self.amp, fee_bps)
+    }
+
+    fn swap_input(
+        &self,
+        amount_out: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        StableSwapMath::calculate_swap_input(amount_out, reserve_in, reserve_out, self.amp, fee_bps)
+    }
+
+    fn initial_lp_tokens(&self, deposit_a: u64, deposit_b: u64) -> Result<u64> {
+        let d = StableSwapMath::compute_d(self.amp, deposit_a, deposit_b)?;
+        u64::try_from(d).map_err(|_| FluxDexError::Overflow.into())
+    }
+
+    /// 失衡时现价偏离储备比例，随放大系数增大趋近 1
+    fn spot_price(&self, reserve_a: u64, reserve_b: u64, a_to_b: bool) -> FixedPoint {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        StableSwapMath::spot_price(self.amp, reserve_in, reserve_out)
+            .unwrap_or(FixedPoint::from_u64(0))
+    }
+}
+
+/// 加权乘积 (Π B_i^w_i = k)
+pub struct WeightedCurve {
+    pub weight_a_bps: u16,
+    pub weight_b_bps: u16,
+}
+
+impl WeightedCurve {
+    /// 按兑换方向返回 (输入权重, 输出权重)
+    fn weights_for(&self, a_to_b: bool) -> (u16, u16) {
+        directional(self.weight_a_bps, self.weight_b_bps, a_to_b)
+    }
+}
+
+impl SwapCurve for WeightedCurve {
+    fn swap_output(
+        &self,
+        amount_in: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        let (weight_in, weight_out) = self.weights_for(a_to_b);
+        WeightedMath::calculate_swap_output(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            weight_in,
+            weight_out,
+            fee_bps,
+        )
+    }
+
+    fn swap_input(
+        &self,
+        amount_out: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        a_to_b: bool,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        let (weight_in, weight_out) = self.weights_for(a_to_b);
+        WeightedMath::calculate_swap_input(
+            amount_out,
+            reserve_in,
+            reserve_out,
+            weight_in,
+            weight_out,
+            fee_bps,
+        )
+    }
+
+    fn initial_lp_tokens(&self, deposit_a: u64, deposit_b: u64) -> Result<u64> {
+        WeightedMath::calculate_invariant(
+            deposit_a,
+            deposit_b,
+            self.weight_a_bps,
+            self.weight_b_bps,
+        )
+    }
+
+    fn spot_price(&self, reserve_a: u64, reserve_b: u64, a_to_b: bool) -> FixedPoint {
+        let (reserve_in, reserve_out) = directional(reserve_a, reserve_b, a_to_b);
+        let (weight_in, weight_out) = self.weights_for(a_to_b);
+        weighted_spot_price(reserve_in, reserve_out, weight_in, weight_out)
+    }
+}
+
+/// 按兑换方向返回 (输入侧, 输出侧)
+fn directional<T>(a: T, b: T, a_to_b: bool) -> (T, T) {
+    if a_to_b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// 现价 (reserve_out / weight_out) / (reserve_in / weight_in)，等权重时即储备量之比
+fn weighted_spot_price(
+    reserve_in: u64,
+    reserve_out: u64,
+    weight_in: u16,
+    weight_out: u16,
+) -> FixedPoint {
+    if reserve_in == 0 || weight_out == 0 {
+        return FixedPoint::from_u64(0);
+    }
+    let price = MathUtils::mul_div(
+        reserve_out as u128 * FixedPoint::SCALE,
+        weight_in as u128,
+        reserve_in as u128 * weight_out as u128,
+    )
+    .unwrap_or(u128::MAX); // 极端储备比例下取上限
+
+    FixedPoint::new(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_product_curve_matches_math_utils() {
+        let curve = ConstantProductCurve;
+        let (reserve_a, reserve_b) = (1_000_000u64, 4_000_000u64);
+
+        let out = curve
+            .swap_output(10_000, reserve_a, reserve_b, true, 30)
+            .unwrap();
+        assert_eq!(
+            out,
+            MathUtils::calculate_amm_output(10_000, reserve_a, reserve_b, 30).unwrap()
+        );
+
+        // 反方向使用 B -> A 的储备
+        let input = curve
+            .swap_input(10_000, reserve_a, reserve_b, false, 30)
+            .unwrap();
+        assert_eq!(
+            input,
+            MathUtils::calculate_amm_input(10_000, reserve_b, reserve_a, 30).unwrap()
+        );
+
+        // 首次存入按几何平均，之后按比例
+        assert_eq!(
+            curve.initial_lp_tokens(1_000_000, 4_000_000).unwrap(),
+            2_000_000
+        );
+        let lp = curve
+            .deposit_lp_tokens(100_000, 400_000, reserve_a, reserve_b, 2_000_000)
+            .unwrap();
+        assert_eq!(lp, 200_000);
+        assert_eq!(
+            curve
+                .withdraw_amounts(200_000, reserve_a, reserve_b, 2_000_000)
+                .unwrap(),
+            (100_000, 400_000)
+        );
+
+        assert_eq!(
+            curve.spot_price(reserve_a, reserve_b, true).value,
+            4 * FixedPoint::SCALE
+        );
+    }
+
+    #[test]
+    fn test_curves_dispatch_through_trait_object() {
+        let curves: [Box<dyn SwapCurve>; 3] = [
+            Box::new(ConstantProductCurve),
+            Box::new(StableSwapCurve { amp: 100 }),
+            Box::new(WeightedCurve {
+                weight_a_bps: 5000,
+                weight_b_bps: 5000,
+            }),
+        ];
+
+        // 平衡池中各曲线都给出合理的输出，StableSwap 滑点最小
+        let outputs: Vec<u64> = curves
+            .iter()
+            .map(|curve| {
+                curve
+                    .swap_output(100_000, 10_000_000, 10_000_000, true, 30)
+                    .unwrap()
+            })
+            .collect();
+        assert!(outputs.iter().all(|&out| out > 0 && out < 100_000));
+        assert!(outputs[1] > outputs[0]);
+        assert!(outputs[0].abs_diff(outputs[2]) < 10);
+    }
+
+    #[test]
+    fn test_stable_swap_spot_price_on_skewed_reserves() {
+        let curve = StableSwapCurve { amp: 100 };
+        let (reserve_a, reserve_b) = (1_000_000_000u64, 3_000_000_000u64);
+
+        // 平衡池中价格为 1
+        let balanced = curve.spot_price(reserve_a, reserve_a, true).value;
+        assert!(balanced.abs_diff(FixedPoint::SCALE) <= FixedPoint::SCALE / 1_000_000);
+
+        // 1:3 失衡池：现价略高于 1，远离储备比例 3，与小额兑换的边际价格一致
+        let price = curve.spot_price(reserve_a, reserve_b, true).value;
+        assert!(price > FixedPoint::SCALE && price < FixedPoint::SCALE * 11 / 10);
+        let probe = 1_000_000u64;
+        let marginal = curve
+            .swap_output(probe, reserve_a, reserve_b, true, 0)
+            .unwrap() as u128
+            * FixedPoint::SCALE
+            / probe as u128;
+        assert!(price.abs_diff(marginal) < FixedPoint::SCALE / 1000);
+
+        // 反方向价格互为倒数
+        let inverse = curve.spot_price(reserve_a, reserve_b, false).value;
+        let product = MathUtils::mul_div(price, inverse, FixedPoint::SCALE).unwrap();
+        assert!(product.abs_diff(FixedPoint::SCALE) < FixedPoint::SCALE / 1_000_000);
+    }
+
+    #[test]
+    fn test_weighted_curve_spot_price_uses_weights() {
+        // 80/20 池：等储备时 A 的价格为 4 个 B
+        let curve = WeightedCurve {
+            weight_a_bps: 8000,
+            weight_b_bps: 2000,
+        };
+        let price = curve.spot_price(1_000_000, 1_000_000, true);
+        assert_eq!(price.value, 4 * FixedPoint::SCALE);
+        let inverse = curve.spot_price(1_000_000, 1_000_000, false);
+        assert_eq!(inverse.value, FixedPoint::SCALE / 4);
+    }
+}
@@ -11,6 +11,7 @@ pub struct FixedPoint {
 impl FixedPoint {
     pub const DECIMALS: u32 = 18;
     pub const SCALE: u128 = 1_000_000_000_000_000_000; // 10^18
+    pub const LN_2: u128 = 693_147_180_559_945_309; // ln(2) * 10^18
 
     pub fn new(value: u128) -> Self {
         Self { value }
@@ -27,12 +28,8 @@ impl FixedPoint {
     }
 
     pub fn mul(&self, other: &Self) -> Result<Self> {
-        let result = self
-            .value
-            .checked_mul(other.value)
-            .ok_or(FluxDexError::Overflow)?
-            .checked_div(Self::SCALE)
-            .ok_or(FluxDexError::DivisionByZero)?;
+        // 乘积以 256 位保存，避免 a * b 在除以 SCALE 之前溢出
+        let result = MathUtils::mul_div(self.value, other.value, Self::SCALE)?;
         Ok(Self::new(result))
     }
 
@@ -48,6 +45,96 @@ impl FixedPoint {
             .ok_or(FluxDexError::DivisionByZero)?;
         Ok(Self::new(result))
     }
+
+    /// 自然对数，结果可能为负，返回 SCALE 精度的有符号定点数
+    pub fn ln(&self) -> Result<i128> {
+        if self.value == 0 {
+            return Err(FluxDexError::InvalidCalculation.into());
+        }
+
+        // x = m · 2^k，m ∈ [1, 2)
+        let mut k: i128 = 0;
+        let mut m = self.value;
+        while m >= 2 * Self::SCALE {
+            m /= 2;
+            k += 1;
+        }
+        while m < Self::SCALE {
+            m *= 2;
+            k -= 1;
+        }
+
+        // ln(m) = 2 · atanh(z)，z = (m - 1) / (m + 1) ∈ [0, 1/3)
+        let z = (m - Self::SCALE) * Self::SCALE / (m + Self::SCALE);
+        let z_squared = z * z / Self::SCALE;
+        let mut term = z;
+        let mut sum = 0u128;
+        let mut n = 1u128;
+        while term > 0 {
+            sum += term / n;
+            term = term * z_squared / Self::SCALE;
+            n += 2;
+        }
+
+        Ok(k * Self::LN_2 as i128 + 2 * sum as i128)
+    }
+
+    /// 自然指数 e^x，x 为 SCALE 精度的有符号定点数
+    pub fn exp(x: i128) -> Result<Self> {
+        // x = k · ln(2) + r，r ∈ [0, ln(2))
+        let ln_2 = Self::LN_2 as i128;
+        let k = x.div_euclid(ln_2);
+        let r = x.rem_euclid(ln_2) as u128;
+
+        // 泰勒展开 e^r = Σ r^n / n!
+        let mut term = Self::SCALE;
+        let mut sum = Self::SCALE;
+        let mut n = 1u128;
+        loop {
+            term = term * r / Self::SCALE / n;
+            if term == 0 {
+                break;
+            }
+            sum += term;
+            n += 1;
+        }
+
+        if k >= 0 {
+            if k > sum.leading_zeros() as i128 {
+                return Err(FluxDexError::Overflow.into());
+            }
+            Ok(Self::new(sum << k))
+        } else if k <= -128 {
+            Ok(Self::new(0))
+        } else {
+            Ok(Self::new(sum >> -k))
+        }
+    }
+
+    /// 幂运算 self^exponent = e^(exponent · ln(self))
+    pub fn pow(&self, exponent: &Self) -> Result<Self> {
+        if exponent.value == 0 {
+            return Ok(Self::new(Self::SCALE));
+        }
+        if self.value == 0 {
+            return Ok(Self::new(0));
+        }
+
+        // 拆分指数的整数与小数部分，避免乘积溢出
+        let ln = self.ln()?;
+        let integer_part = ln
+            .checked_mul((exponent.value / Self::SCALE) as i128)
+            .ok_or(FluxDexError::Overflow)?;
+        let fraction_part = ln
+            .checked_mul((exponent.value % Self::SCALE) as i128)
+            .ok_or(FluxDexError::Overflow)?
+            / Self::SCALE as i128;
+        let product = integer_part
+            .checked_add(fraction_part)
+            .ok_or(FluxDexError::Overflow)?;
+
+        Self::exp(product)
+    }
 }
 
 /// 数学工具库
@@ -94,6 +181,61 @@ impl MathUtils {
         Ok(output as u64)
     }
 
+    /// 计算恒定乘积AMM获得指定输出量所需的输入量（反向公式）
+    ///
+    /// 结果向上取整，舍入误差由交易者承担
+    pub fn calculate_amm_input(
+        output_amount: u64,
+        input_reserve: u64,
+        output_reserve: u64,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        // 检查输入参数
+        if output_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 || output_amount >= output_reserve {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let fee_factor = 10000u128
+            .checked_sub(fee_bps as u128)
+            .ok_or(FluxDexError::InvalidCalculation)?;
+
+        let numerator = (input_reserve as u128)
+            .checked_mul(output_amount as u128)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_mul(10000)
+            .ok_or(FluxDexError::Overflow)?;
+
+        let denominator = ((output_reserve - output_amount) as u128)
+            .checked_mul(fee_factor)
+            .ok_or(FluxDexError::Overflow)?;
+
+        if denominator == 0 {
+            return Err(FluxDexError::DivisionByZero.into());
+        }
+
+        // 向上取整
+        let input = numerator
+            .checked_add(denominator - 1)
+            .ok_or(FluxDexError::Overflow)?
+            / denominator;
+
+        u64::try_from(input).map_err(|_| FluxDexError::Overflow.into())
+    }
+
+    /// 计算输入量中包含的手续费
+    pub fn calculate_fee_amount(input_amount: u64, fee_bps: u16) -> Result<u64> {
+        let fee = (input_amount as u128)
+            .checked_mul(fee_bps as u128)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_div(10000)
+            .ok_or(FluxDexError::DivisionByZero)?;
+
+        Ok(fee as u64)
+    }
+
     /// 计算动态费率（基于波动性和流动性）
     pub fn calculate_dynamic_fee(
         base_fee: u16,
@@ -125,6 +267,51 @@ impl MathUtils {
         Ok(adjusted_fee as u16)
     }
 
+    /// 以 EWMA 更新波动率分数：新收益率 = |当前价格 - 基准价格| / 基准价格
+    pub fn calculate_volatility_ewma(
+        previous_score: u16, // 0-10000
+        reference_price: FixedPoint,
+        current_price: FixedPoint,
+        alpha_bps: u16, // 平滑系数
+    ) -> Result<u16> {
+        if previous_score > 10000 || alpha_bps > 10000 {
+            return Err(FluxDexError::InvalidCalculation.into());
+        }
+        if reference_price.value == 0 {
+            return Ok(previous_score);
+        }
+
+        // 收益率封顶 100%，价格差极大时商超出 u128 也按封顶处理
+        let return_bps = Self::mul_div(
+            reference_price.value.abs_diff(current_price.value),
+            10000,
+            reference_price.value,
+        )
+        .map_or(10000, |return_bps| return_bps.min(10000));
+
+        let score = (previous_score as u128 * (10000 - alpha_bps as u128)
+            + return_bps * alpha_bps as u128)
+            / 10000;
+
+        Ok(score as u16)
+    }
+
+    /// 根据池子深度计算流动性分数 (0-10000)
+    pub fn calculate_liquidity_score(
+        reserve_a: u64,
+        reserve_b: u64,
+        full_depth: u64, // 满分对应的 sqrt(reserve_a * reserve_b)
+    ) -> Result<u16> {
+        if full_depth == 0 {
+            return Err(FluxDexError::DivisionByZero.into());
+        }
+
+        let depth = Self::sqrt(reserve_a as u128 * reserve_b as u128)?;
+        let score = depth.checked_mul(10000).ok_or(FluxDexError::Overflow)? / full_depth as u128;
+
+        Ok(score.min(10000) as u16)
+    }
+
     /// 计算价格影响
     pub fn calculate_price_impact(
         input_amount: u64,
@@ -193,6 +380,58 @@ impl MathUtils {
         Ok(lp_from_a.min(lp_from_b) as u64)
     }
 
+    /// 计算单边存入时需要先兑换掉的输入数量
+    ///
+    /// 手续费不计入储备，兑换后输入侧储备只增加 (1-f)·s。记 g = 1-f、u = s/r、t = a/r，
+    /// 求解 g²·u² + (1+g)·u - t = 0，使兑换后剩余输入与兑换所得恰好符合新的池子比例
+    pub fn calculate_zap_swap_amount(
+        input_amount: u64,
+        input_reserve: u64,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        const ZAP_SCALE: u128 = 1_000_000_000_000; // 10^12
+
+        if input_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let fee_factor = 10000u128
+            .checked_sub(fee_bps as u128)
+            .ok_or(FluxDexError::InvalidCalculation)?;
+        if fee_factor == 0 {
+            return Err(FluxDexError::InvalidFeeTier.into());
+        }
+
+        // g 与 1 + g 放大 ZAP_SCALE 倍，g² 放大 ZAP_SCALE² 倍
+        let g = fee_factor * (ZAP_SCALE / 10000);
+        let b = ZAP_SCALE + g;
+        let g_squared = g * g;
+
+        // t = a / r
+        let ratio = Self::mul_div(input_amount as u128, ZAP_SCALE, input_reserve as u128)?;
+
+        // sqrt((1+g)² + 4·g²·t)，放大 ZAP_SCALE 倍
+        let discriminant = (b * b)
+            .checked_add(Self::mul_div(4 * g_squared, ratio, ZAP_SCALE)?)
+            .ok_or(FluxDexError::Overflow)?;
+        let root = Self::sqrt(discriminant)?;
+
+        // s = (root - (1+g)) · r / (2·g²)
+        let numerator = root
+            .checked_sub(b)
+            .ok_or(FluxDexError::Underflow)?
+            .checked_mul(input_reserve as u128)
+            .ok_or(FluxDexError::Overflow)?;
+        let swap_amount = Self::mul_div(numerator, ZAP_SCALE, 2 * g_squared)?;
+
+        Ok(u64::try_from(swap_amount)
+            .unwrap_or(u64::MAX)
+            .min(input_amount))
+    }
+
     /// 计算移除流动性时的资产数量
     pub fn calculate_remove_amounts(
         lp_tokens: u64,
@@ -231,7 +470,7 @@ impl MathUtils {
         volatility: u16,  // 历史波动率
         risk_profile: u8, // 风险档次: 1=保守, 2=平衡, 3=激进
     ) -> Result<(FixedPoint, FixedPoint)> {
-        if risk_profile < 1 || risk_profile > 3 {
+        if !(1..=3).contains(&risk_profile) {
             return Err(FluxDexError::InvalidRiskProfile.into());
         }
 
@@ -288,6 +527,64 @@ impl MathUtils {
 
         Ok(result)
     }
+
+    /// 计算 a * b / c（向下取整），乘积以 256 位保存，避免 u128 中间溢出
+    pub fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+        Ok(Self::mul_div_rem(a, b, c)?.0)
+    }
+
+    /// 计算 a * b / c（向上取整）
+    pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Result<u128> {
+        let (quotient, remainder) = Self::mul_div_rem(a, b, c)?;
+        if remainder > 0 {
+            quotient.checked_add(1).ok_or(FluxDexError::Overflow.into())
+        } else {
+            Ok(quotient)
+        }
+    }
+
+    /// 返回 (a * b / c, a * b % c)
+    fn mul_div_rem(a: u128, b: u128, c: u128) -> Result<(u128, u128)> {
+        if c == 0 {
+            return Err(FluxDexError::DivisionByZero.into());
+        }
+        if let Some(product) = a.checked_mul(b) {
+            return Ok((product / c, product % c));
+        }
+
+        // 按 64 位拆分计算 256 位乘积 (hi, lo)
+        const MASK: u128 = u64::MAX as u128;
+        let (a_hi, a_lo) = (a >> 64, a & MASK);
+        let (b_hi, b_lo) = (b >> 64, b & MASK);
+        let lo_lo = a_lo * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_lo = a_hi * b_lo;
+        let hi_hi = a_hi * b_hi;
+
+        let mid = (lo_lo >> 64) + (lo_hi & MASK) + (hi_lo & MASK);
+        let lo = (lo_lo & MASK) | (mid << 64);
+        let hi = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (mid >> 64);
+
+        // 商必须能放进 u128
+        if hi >= c {
+            return Err(FluxDexError::Overflow.into());
+        }
+
+        // 逐位长除法
+        let mut remainder = hi;
+        let mut quotient = 0u128;
+        for i in (0..128).rev() {
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | ((lo >> i) & 1);
+            quotient <<= 1;
+            if carry == 1 || remainder >= c {
+                remainder = remainder.wrapping_sub(c);
+                quotient |= 1;
+            }
+        }
+
+        Ok((quotient, remainder))
+    }
 }
 
 #[cfg(test)]
@@ -321,6 +618,100 @@ mod tests {
         assert!(output < 2000); // 应该小于无滑点情况下的输出
     }
 
+    #[test]
+    fn test_amm_input_rounds_against_trader() {
+        let input = MathUtils::calculate_amm_input(
+            1000,  // desired output
+            10000, // input reserve
+            20000, // output reserve
+            300,   // 3% fee
+        )
+        .unwrap();
+
+        // 反向计算的输入量必须至少换出目标数量
+        let output = MathUtils::calculate_amm_output(input, 10000, 20000, 300).unwrap();
+        assert!(output >= 1000);
+
+        // 少 1 个单位则不足
+        let output = MathUtils::calculate_amm_output(input - 1, 10000, 20000, 300).unwrap();
+        assert!(output < 1000);
+
+        // 不能取走全部储备
+        assert!(MathUtils::calculate_amm_input(20000, 10000, 20000, 300).is_err());
+    }
+
+    #[test]
+    fn test_zap_swap_amount_balances_deposit() {
+        let (amount, reserve_in, reserve_out, fee) = (100_000u64, 1_000_000u64, 2_000_000u64, 30);
+
+        let swap_amount = MathUtils::calculate_zap_swap_amount(amount, reserve_in, fee).unwrap();
+        assert!(swap_amount > 0 && swap_amount < amount / 2);
+
+        let out =
+            MathUtils::calculate_amm_output(swap_amount, reserve_in, reserve_out, fee).unwrap();
+        // 与 Pool::record_swap 一致：手续费不计入储备
+        let fee_amount = MathUtils::calculate_fee_amount(swap_amount, fee).unwrap();
+        let new_reserve_in = (reserve_in + swap_amount - fee_amount) as u128;
+        let new_reserve_out = (reserve_out - out) as u128;
+
+        // 剩余输入与兑换所得应符合兑换后的池子比例，误差只来自取整
+        let remaining = (amount - swap_amount) as u128;
+        let balanced_remaining = out as u128 * new_reserve_in / new_reserve_out;
+        assert!(remaining >= balanced_remaining);
+        assert!(remaining - balanced_remaining <= 2);
+    }
+
+    #[test]
+    fn test_volatility_ewma() {
+        let reference = FixedPoint::from_u64(100);
+
+        // 价格不变时分数按 alpha 衰减
+        let score = MathUtils::calculate_volatility_ewma(5000, reference, reference, 2000).unwrap();
+        assert_eq!(score, 4000);
+
+        // 价格变动 10% (1000 bps)
+        let score =
+            MathUtils::calculate_volatility_ewma(0, reference, FixedPoint::from_u64(110), 2000)
+                .unwrap();
+        assert_eq!(score, 200);
+
+        // 收益率封顶 100%
+        let score =
+            MathUtils::calculate_volatility_ewma(0, reference, FixedPoint::from_u64(1000), 10000)
+                .unwrap();
+        assert_eq!(score, 10000);
+
+        // 极端价格下不溢出，按封顶处理
+        let score = MathUtils::calculate_volatility_ewma(
+            0,
+            FixedPoint::new(1),
+            FixedPoint::new(u128::MAX),
+            10000,
+        )
+        .unwrap();
+        assert_eq!(score, 10000);
+        let score = MathUtils::calculate_volatility_ewma(
+            0,
+            FixedPoint::new(u128::MAX / 2),
+            FixedPoint::new(u128::MAX),
+            10000,
+        )
+        .unwrap();
+        assert_eq!(score, 10000);
+    }
+
+    #[test]
+    fn test_liquidity_score() {
+        assert_eq!(
+            MathUtils::calculate_liquidity_score(1_000, 1_000, 10_000).unwrap(),
+            1000
+        );
+        assert_eq!(
+            MathUtils::calculate_liquidity_score(1_000_000, 1_000_000, 10_000).unwrap(),
+            10000
+        );
+    }
+
     #[test]
     fn test_dynamic_fee() {
         let fee = MathUtils::calculate_dynamic_fee(
@@ -333,4 +724,47 @@ mod tests {
         assert!(fee > 300); // 应该高于基础费率
         assert!(fee <= 10000); // 不应超过最大费率
     }
+
+    #[test]
+    fn test_fixed_point_ln_exp_pow() {
+        let tolerance = 1_000u128; // 1e-15
+
+        assert_eq!(FixedPoint::from_u64(1).ln().unwrap(), 0);
+        let ln_2 = FixedPoint::from_u64(2).ln().unwrap();
+        assert!(ln_2.abs_diff(FixedPoint::LN_2 as i128) < tolerance);
+        let ln_half = FixedPoint::new(FixedPoint::SCALE / 2).ln().unwrap();
+        assert!(ln_half.abs_diff(-(FixedPoint::LN_2 as i128)) < tolerance);
+
+        let e = FixedPoint::exp(FixedPoint::SCALE as i128).unwrap();
+        assert!(e.value.abs_diff(2_718_281_828_459_045_235) < tolerance);
+
+        // 9^0.5 = 3
+        let root = FixedPoint::from_u64(9)
+            .pow(&FixedPoint::new(FixedPoint::SCALE / 2))
+            .unwrap();
+        assert!(root.value.abs_diff(3 * FixedPoint::SCALE) < tolerance);
+
+        // 0.5^4 = 0.0625
+        let power = FixedPoint::new(FixedPoint::SCALE / 2)
+            .pow(&FixedPoint::from_u64(4))
+            .unwrap();
+        assert!(power.value.abs_diff(FixedPoint::SCALE / 16) < tolerance);
+    }
+
+    #[test]
+    fn test_mul_div() {
+        assert_eq!(MathUtils::mul_div(6, 7, 4).unwrap(), 10);
+
+        // 乘积超过 u128
+        let a = u128::MAX / 3;
+        assert_eq!(MathUtils::mul_div(a, 6, 3).unwrap(), a * 2);
+        assert_eq!(
+            MathUtils::mul_div(u128::MAX, u128::MAX, u128::MAX).unwrap(),
+            u128::MAX
+        );
+
+        // 商溢出
+        assert!(MathUtils::mul_div(u128::MAX, 2, 1).is_err());
+        assert!(MathUtils::mul_div(1, 1, 0).is_err());
+    }
 }
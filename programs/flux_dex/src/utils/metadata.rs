@@ -0,0 +1,111 @@
+// programs/flux_dex/src/utils/metadata.rs
+use crate::state::{LP_TOKEN_NAME_PREFIX, PAIR_SYMBOL_MAX_LEN};
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+
+/// 读取代币 Metaplex 元数据中的符号
+///
+/// 元数据账户不存在或无法解析时退回 mint 地址的前几位，保证任意交易对都能建池
+pub fn token_symbol(mint: &Pubkey, metadata: &AccountInfo) -> String {
+    let symbol = if *metadata.owner == mpl_token_metadata::ID {
+        metadata
+            .try_borrow_data()
+            .ok()
+            .and_then(|data| Metadata::safe_deserialize(&data).ok())
+            .map(|metadata| trim_padding(&metadata.symbol).to_string())
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    if symbol.is_empty() {
+        truncate(&mint.to_string(), PAIR_SYMBOL_MAX_LEN).to_string()
+    } else {
+        symbol
+    }
+}
+
+/// 由交易对符号生成 LP 代币的名称和符号
+///
+/// 名称形如 "FLUX-LP SOL/USDC"，符号形如 "SOL-USDC"，均在 Metaplex 长度上限之内
+pub fn lp_token_name_and_symbol(symbol_a: &str, symbol_b: &str) -> (String, String) {
+    let name = format!(
+        "{} {}/{}",
+        LP_TOKEN_NAME_PREFIX,
+        truncate(symbol_a, mpl_token_metadata::MAX_SYMBOL_LENGTH),
+        truncate(symbol_b, mpl_token_metadata::MAX_SYMBOL_LENGTH),
+    );
+    let symbol = format!(
+        "{}-{}",
+        truncate(symbol_a, PAIR_SYMBOL_MAX_LEN),
+        truncate(symbol_b, PAIR_SYMBOL_MAX_LEN),
+    );
+    (name, symbol)
+}
+
+/// 去掉 Metaplex 写入时补齐的 \0 填充
+pub fn trim_padding(value: &str) -> &str {
+    value.trim_end_matches('\0').trim()
+}
+
+/// 按字节截断，不拆分多字节字符
+fn truncate(value: &str, max_len: usize) -> &str {
+    if value.len() <= max_len {
+        return value;
+    }
+    let mut end = max_len;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lp_token_name_and_symbol() {
+        let (name, symbol) = lp_token_name_and_symbol("SOL", "USDC");
+        assert_eq!(name, "FLUX-LP SOL/USDC");
+        assert_eq!(symbol, "SOL-USDC");
+
+        // 超长符号截断后仍在 Metaplex 限制之内
+        let (name, symbol) = lp_token_name_and_symbol("VERYLONGSYMBOL", "ANOTHERLONGONE");
+        assert_eq!(name, "FLUX-LP VERYLONGSY/ANOTHERLON");
+        assert_eq!(symbol, "VERY-ANOT");
+        assert!(name.len() <= mpl_token_metadata::MAX_NAME_LENGTH);
+        assert!(symbol.len() <= mpl_token_metadata::MAX_SYMBOL_LENGTH);
+    }
+
+    #[test]
+    fn test_truncate_keeps_char_boundary() {
+        // "币" 占 3 字节，截断不能落在字符中间
+        assert_eq!(truncate("币币", 4), "币");
+        assert_eq!(truncate("SOL", 4), "SOL");
+        assert_eq!(trim_padding("SOL\0\0\0"), "SOL");
+    }
+
+    #[test]
+    fn test_token_symbol_falls_back_to_mint_address() {
+        let mint = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::default();
+        let mut lamports = 0;
+        let mut data = [];
+        let metadata = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        let symbol = token_symbol(&mint, &metadata);
+        assert_eq!(symbol, mint.to_string()[..PAIR_SYMBOL_MAX_LEN]);
+    }
+}
@@ -1,3 +1,15 @@
+pub mod concentrated;
+pub mod curve;
 pub mod math;
+pub mod metadata;
+pub mod stable_swap;
+pub mod token;
+pub mod weighted;
 
+pub use concentrated::*;
+pub use curve::*;
 pub use math::*;
+pub use metadata::*;
+pub use stable_swap::*;
+pub use token::*;
+pub use weighted::*;
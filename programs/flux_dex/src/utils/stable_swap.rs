@@ -0,0 +1,261 @@
+// programs/flux_dex/src/utils/stable_swap.rs
+use crate::error::FluxDexError;
+use crate::utils::{FixedPoint, MathUtils};
+use anchor_lang::prelude::*;
+
+/// 双币 StableSwap 不变式：A·n^n·Σx + D = A·D·n^n + D^(n+1) / (n^n·Πx)
+pub struct StableSwapMath;
+
+impl StableSwapMath {
+    /// 代币数量
+    const N_COINS: u128 = 2;
+
+    /// 牛顿迭代最大次数
+    const MAX_ITERATIONS: u8 = 255;
+
+    /// 计算不变量 D
+    pub fn compute_d(amp: u64, reserve_a: u64, reserve_b: u64) -> Result<u128> {
+        let sum = reserve_a as u128 + reserve_b as u128;
+        if sum == 0 {
+            return Ok(0);
+        }
+        if reserve_a == 0 || reserve_b == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        // Ann = A · n^n
+        let ann = (amp as u128)
+            .checked_mul(Self::N_COINS * Self::N_COINS)
+            .ok_or(FluxDexError::Overflow)?;
+
+        let mut d = sum;
+        for _ in 0..Self::MAX_ITERATIONS {
+            // D_P = D^(n+1) / (n^n · Πx)
+            let mut d_p = d;
+            d_p = MathUtils::mul_div(d_p, d, reserve_a as u128 * Self::N_COINS)?;
+            d_p = MathUtils::mul_div(d_p, d, reserve_b as u128 * Self::N_COINS)?;
+
+            // D = (Ann·S + n·D_P)·D / ((Ann - 1)·D + (n + 1)·D_P)
+            let numerator = ann
+                .checked_mul(sum)
+                .and_then(|v| v.checked_add(d_p.checked_mul(Self::N_COINS)?))
+                .ok_or(FluxDexError::Overflow)?;
+            let denominator = (ann - 1)
+                .checked_mul(d)
+                .and_then(|v| v.checked_add(d_p.checked_mul(Self::N_COINS + 1)?))
+                .ok_or(FluxDexError::Overflow)?;
+
+            let d_prev = d;
+            d = MathUtils::mul_div(numerator, d, denominator)?;
+
+            if d.abs_diff(d_prev) <= 1 {
+                return Ok(d);
+            }
+        }
+
+        Err(FluxDexError::LiquidityCalculationFailed.into())
+    }
+
+    /// 已知一侧新储备量与不变量 D，求另一侧储备量 y
+    pub fn compute_y(amp: u64, new_reserve: u128, d: u128) -> Result<u128> {
+        if new_reserve == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let ann = (amp as u128)
+            .checked_mul(Self::N_COINS * Self::N_COINS)
+            .ok_or(FluxDexError::Overflow)?;
+
+        // c = D^(n+1) / (n^n · x · Ann)，b = x + D / Ann
+        let mut c = d;
+        c = MathUtils::mul_div(c, d, new_reserve * Self::N_COINS)?;
+        c = MathUtils::mul_div(c, d, ann * Self::N_COINS)?;
+        let b = new_reserve
+            .checked_add(d / ann)
+            .ok_or(FluxDexError::Overflow)?;
+
+        // y = (y² + c) / (2y + b - D)
+        let mut y = d;
+        for _ in 0..Self::MAX_ITERATIONS {
+            let numerator = y
+                .checked_mul(y)
+                .and_then(|v| v.checked_add(c))
+                .ok_or(FluxDexError::Overflow)?;
+            let denominator = y
+                .checked_mul(2)
+                .and_then(|v| v.checked_add(b))
+                .and_then(|v| v.checked_sub(d))
+                .ok_or(FluxDexError::InvalidCalculation)?;
+            if denominator == 0 {
+                return Err(FluxDexError::DivisionByZero.into());
+            }
+
+            let y_prev = y;
+            y = numerator / denominator;
+
+            if y.abs_diff(y_prev) <= 1 {
+                return Ok(y);
+            }
+        }
+
+        Err(FluxDexError::LiquidityCalculationFailed.into())
+    }
+
+    /// 现价：不变式在当前储备处的边际价格 -dy/dx，以输出代币计价
+    ///
+    /// 记 D_P = D³ / (4xy)，则价格 = y·(Ann·x + D_P) / (x·(Ann·y + D_P))
+    pub fn spot_price(amp: u64, input_reserve: u64, output_reserve: u64) -> Result<FixedPoint> {
+        if input_reserve == 0 || output_reserve == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let ann = (amp as u128)
+            .checked_mul(Self::N_COINS * Self::N_COINS)
+            .ok_or(FluxDexError::Overflow)?;
+        let (x, y) = (input_reserve as u128, output_reserve as u128);
+
+        let d = Self::compute_d(amp, input_reserve, output_reserve)?;
+        let mut d_p = d;
+        d_p = MathUtils::mul_div(d_p, d, x * Self::N_COINS)?;
+        d_p = MathUtils::mul_div(d_p, d, y * Self::N_COINS)?;
+
+        let numerator = ann
+            .checked_mul(x)
+            .and_then(|v| v.checked_add(d_p))
+            .ok_or(FluxDexError::Overflow)?;
+        let denominator = ann
+            .checked_mul(y)
+            .and_then(|v| v.checked_add(d_p))
+            .ok_or(FluxDexError::Overflow)?;
+
+        let ratio = MathUtils::mul_div(numerator, FixedPoint::SCALE, denominator)?;
+        Ok(FixedPoint::new(MathUtils::mul_div(ratio, y, x)?))
+    }
+
+    /// 计算兑换输出量，手续费从输入中扣除，结果向下取整
+    pub fn calculate_swap_output(
+        input_amount: u64,
+        input_reserve: u64,
+        output_reserve: u64,
+        amp: u64,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        if input_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 || output_reserve == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let fee_amount = MathUtils::calculate_fee_amount(input_amount, fee_bps)?;
+        let input_after_fee = (input_amount - fee_amount) as u128;
+
+        let d = Self::compute_d(amp, input_reserve, output_reserve)?;
+        let new_input_reserve = (input_reserve as u128)
+            .checked_add(input_after_fee)
+            .ok_or(FluxDexError::Overflow)?;
+        let new_output_reserve = Self::compute_y(amp, new_input_reserve, d)?;
+
+        // 额外减 1 抵消迭代误差，舍入由交易者承担
+        let output = (output_reserve as u128)
+            .saturating_sub(new_output_reserve)
+            .saturating_sub(1);
+
+        Ok(output as u64)
+    }
+
+    /// 计算获得指定输出量所需的输入量（含手续费），结果向上取整
+    pub fn calculate_swap_input(
+        output_amount: u64,
+        input_reserve: u64,
+        output_reserve: u64,
+        amp: u64,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        if output_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 || output_amount >= output_reserve {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+
+        let d = Self::compute_d(amp, input_reserve, output_reserve)?;
+        let new_output_reserve = (output_reserve - output_amount) as u128;
+        let new_input_reserve = Self::compute_y(amp, new_output_reserve, d)?;
+
+        // 额外加 1 抵消迭代误差
+        let input_after_fee = new_input_reserve
+            .checked_sub(input_reserve as u128)
+            .ok_or(FluxDexError::InvalidCalculation)?
+            .checked_add(1)
+            .ok_or(FluxDexError::Overflow)?;
+
+        let fee_factor = 10000u128
+            .checked_sub(fee_bps as u128)
+            .ok_or(FluxDexError::InvalidCalculation)?;
+        if fee_factor == 0 {
+            return Err(FluxDexError::InvalidFeeTier.into());
+        }
+
+        // 向上取整
+        let input = input_after_fee
+            .checked_mul(10000)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_add(fee_factor - 1)
+            .ok_or(FluxDexError::Overflow)?
+            / fee_factor;
+
+        u64::try_from(input).map_err(|_| FluxDexError::Overflow.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_d_balanced_pool() {
+        // 储备平衡时 D 等于储备之和
+        let d = StableSwapMath::compute_d(100, 1_000_000, 1_000_000).unwrap();
+        assert_eq!(d, 2_000_000);
+
+        // 大额储备不会溢出
+        let d =
+            StableSwapMath::compute_d(1000, 10_000_000_000_000_000, 9_000_000_000_000_000).unwrap();
+        assert!(d > 18_900_000_000_000_000 && d < 19_000_000_000_000_000);
+    }
+
+    #[test]
+    fn test_stable_swap_output_near_parity() {
+        let reserve = 1_000_000_000_000u64;
+        let amount_in = 1_000_000_000u64;
+
+        let stable_out =
+            StableSwapMath::calculate_swap_output(amount_in, reserve, reserve, 100, 0).unwrap();
+        let cp_out = MathUtils::calculate_amm_output(amount_in, reserve, reserve, 0).unwrap();
+
+        // 滑点远低于恒定乘积
+        assert!(stable_out > cp_out);
+        assert!(stable_out <= amount_in);
+        assert!((amount_in - stable_out) * 100 < amount_in - cp_out);
+    }
+
+    #[test]
+    fn test_stable_swap_input_rounds_against_trader() {
+        let (reserve_in, reserve_out) = (5_000_000_000u64, 4_000_000_000u64);
+        let amount_out = 100_000_000u64;
+
+        let amount_in =
+            StableSwapMath::calculate_swap_input(amount_out, reserve_in, reserve_out, 200, 30)
+                .unwrap();
+        let out =
+            StableSwapMath::calculate_swap_output(amount_in, reserve_in, reserve_out, 200, 30)
+                .unwrap();
+        assert!(out >= amount_out);
+
+        let out_less =
+            StableSwapMath::calculate_swap_output(amount_in - 2, reserve_in, reserve_out, 200, 30)
+                .unwrap();
+        assert!(out_less <= amount_out);
+    }
+}
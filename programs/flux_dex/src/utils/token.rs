@@ -0,0 +1,268 @@
+// programs/flux_dex/src/utils/token.rs
+use crate::error::FluxDexError;
+use crate::state::seeds::*;
+use crate::state::Pool;
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface;
+use anchor_spl::token_interface::Burn;
+use anchor_spl::token_interface::FreezeAccount;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::MintTo;
+use anchor_spl::token_interface::ThawAccount;
+use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::token_interface::TokenInterface;
+use anchor_spl::token_interface::TransferChecked;
+
+/// 池子支持的 Token-2022 mint 扩展
+///
+/// 转账钩子、永久委托、不可转让等扩展会让金库余额脱离池子控制，不予支持
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 4] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+];
+
+/// 转账代币：用户 -> 池子金库，返回金库实际到账的数量
+///
+/// 带转账手续费的 mint 到账数量少于 amount，储备量须按返回值记账
+pub fn transfer_tokens_to_pool<'info>(
+    user: &Signer<'info>,
+    user_token_account: &InterfaceAccount<'info, TokenAccount>,
+    pool_vault: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<u64> {
+    pool_vault.reload()?;
+    let balance_before = pool_vault.amount;
+
+    let cpi_accounts = TransferChecked {
+        from: user_token_account.to_account_info(),
+        mint: mint.to_account_info(),
+        to: pool_vault.to_account_info(),
+        authority: user.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+
+    pool_vault.reload()?;
+    let received = pool_vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(FluxDexError::Underflow)?;
+    Ok(received)
+}
+
+/// 转账代币：池子金库 -> 用户（由 Pool PDA 签名）
+pub fn transfer_tokens_from_pool<'info>(
+    pool: &Account<'info, Pool>,
+    pool_vault: &InterfaceAccount<'info, TokenAccount>,
+    user_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    let fee_tier = pool.fee_tier.to_le_bytes();
+    let curve_type = [pool.curve_type as u8];
+    let authority_seed = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &fee_tier[..],
+        &curve_type[..],
+        &[pool.bump],
+    ];
+    let signer = &[&authority_seed[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: pool_vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to: user_token_account.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    Ok(())
+}
+
+/// 铸造 LP 代币（由 Pool PDA 签名）
+///
+/// LP 不可转让：持有 LP 的账户始终处于冻结状态，铸造前解冻、铸造后重新冻结
+pub fn mint_lp_tokens<'info>(
+    pool: &Account<'info, Pool>,
+    lp_mint: &InterfaceAccount<'info, Mint>,
+    user_lp_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    let fee_tier = pool.fee_tier.to_le_bytes();
+    let curve_type = [pool.curve_type as u8];
+    let authority_seed = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &fee_tier[..],
+        &curve_type[..],
+        &[pool.bump],
+    ];
+    let signer = &[&authority_seed[..]];
+
+    if user_lp_account.is_frozen() {
+        thaw_lp_account(pool, lp_mint, user_lp_account, token_program, signer)?;
+    }
+
+    let cpi_accounts = MintTo {
+        mint: lp_mint.to_account_info(),
+        to: user_lp_account.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+    token_interface::mint_to(cpi_ctx, amount)?;
+
+    freeze_lp_account(pool, lp_mint, user_lp_account, token_program, signer)
+}
+
+/// 销毁用户的 LP 代币（由用户签名）
+///
+/// 销毁前由 Pool PDA 解冻，仍有余额时重新冻结；余额归零的账户保持解冻，用户可以关闭
+pub fn burn_lp_tokens<'info>(
+    pool: &Account<'info, Pool>,
+    user: &Signer<'info>,
+    lp_mint: &InterfaceAccount<'info, Mint>,
+    user_lp_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    let fee_tier = pool.fee_tier.to_le_bytes();
+    let curve_type = [pool.curve_type as u8];
+    let authority_seed = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &fee_tier[..],
+        &curve_type[..],
+        &[pool.bump],
+    ];
+    let signer = &[&authority_seed[..]];
+
+    if user_lp_account.is_frozen() {
+        thaw_lp_account(pool, lp_mint, user_lp_account, token_program, signer)?;
+    }
+
+    let cpi_accounts = Burn {
+        mint: lp_mint.to_account_info(),
+        from: user_lp_account.to_account_info(),
+        authority: user.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+    token_interface::burn(cpi_ctx, amount)?;
+
+    if user_lp_account.amount > amount {
+        freeze_lp_account(pool, lp_mint, user_lp_account, token_program, signer)?;
+    }
+    Ok(())
+}
+
+fn freeze_lp_account<'info>(
+    pool: &Account<'info, Pool>,
+    lp_mint: &InterfaceAccount<'info, Mint>,
+    lp_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_accounts = FreezeAccount {
+        account: lp_account.to_account_info(),
+        mint: lp_mint.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+    token_interface::freeze_account(cpi_ctx)
+}
+
+fn thaw_lp_account<'info>(
+    pool: &Account<'info, Pool>,
+    lp_mint: &InterfaceAccount<'info, Mint>,
+    lp_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_accounts = ThawAccount {
+        account: lp_account.to_account_info(),
+        mint: lp_mint.to_account_info(),
+        authority: pool.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+    token_interface::thaw_account(cpi_ctx)
+}
+
+/// 转出 amount 时 mint 收取的转账手续费
+pub fn transfer_fee_amount(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let data = mint_info.try_borrow_data()?;
+    let Some(fee_config) = transfer_fee_config(&mint_info, &data)? else {
+        return Ok(0);
+    };
+    fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(FluxDexError::Overflow.into())
+}
+
+/// 扣除转账手续费后到账 net_amount 所需转出的数量
+pub fn amount_with_transfer_fee(mint: &InterfaceAccount<Mint>, net_amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let data = mint_info.try_borrow_data()?;
+    let Some(fee_config) = transfer_fee_config(&mint_info, &data)? else {
+        return Ok(net_amount);
+    };
+    let fee = fee_config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, net_amount)
+        .ok_or(FluxDexError::Overflow)?;
+    net_amount
+        .checked_add(fee)
+        .ok_or(FluxDexError::Overflow.into())
+}
+
+/// 检查 mint 只启用了池子支持的扩展
+pub fn validate_mint_extensions(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != anchor_spl::token_2022::ID {
+        return Ok(());
+    }
+    let data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+    for extension in mint_state.get_extension_types()? {
+        if !SUPPORTED_MINT_EXTENSIONS.contains(&extension) {
+            return Err(FluxDexError::UnsupportedMintExtension.into());
+        }
+    }
+    Ok(())
+}
+
+fn transfer_fee_config(mint_info: &AccountInfo, data: &[u8]) -> Result<Option<TransferFeeConfig>> {
+    if *mint_info.owner != anchor_spl::token_2022::ID {
+        return Ok(None);
+    }
+    let mint_state = StateWithExtensions::<MintState>::unpack(data)?;
+    Ok(mint_state
+        .get_extension::<TransferFeeConfig>()
+        .ok()
+        .copied())
+}
@@ -0,0 +1,266 @@
+// programs/flux_dex/src/utils/weighted.rs
+use crate::error::FluxDexError;
+use crate::state::{MAX_IN_RATIO_BPS, MAX_OUT_RATIO_BPS};
+use crate::utils::{FixedPoint, MathUtils};
+use anchor_lang::prelude::*;
+
+/// 加权乘积不变式：V = Π B_i^w_i，权重以 bps 表示且总和为 10000
+pub struct WeightedMath;
+
+impl WeightedMath {
+    /// pow 的相对误差上限 (1e-14)，舍入时偏向池子
+    const MAX_POW_RELATIVE_ERROR: u128 = 10_000;
+
+    /// 计算兑换输出量：Ao = Bo · (1 - (Bi / (Bi + Ai))^(wi / wo))
+    pub fn calculate_swap_output(
+        input_amount: u64,
+        input_reserve: u64,
+        output_reserve: u64,
+        weight_in_bps: u16,
+        weight_out_bps: u16,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        if input_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 || output_reserve == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+        // 单笔输入过大时 pow 误差放大
+        if input_amount as u128 * 10000 > input_reserve as u128 * MAX_IN_RATIO_BPS as u128 {
+            return Err(FluxDexError::PriceImpactTooHigh.into());
+        }
+
+        let fee_amount = MathUtils::calculate_fee_amount(input_amount, fee_bps)?;
+        let input_after_fee = (input_amount - fee_amount) as u128;
+
+        // 底数向上取整，输出随之偏小
+        let base = Self::div_up(
+            input_reserve as u128 * FixedPoint::SCALE,
+            input_reserve as u128 + input_after_fee,
+        )?;
+        let power = Self::pow_up(base, Self::weight_ratio(weight_in_bps, weight_out_bps)?)?
+            .min(FixedPoint::SCALE);
+
+        let output = MathUtils::mul_div(
+            output_reserve as u128,
+            FixedPoint::SCALE - power,
+            FixedPoint::SCALE,
+        )?;
+
+        Ok(output as u64)
+    }
+
+    /// 计算获得指定输出量所需的输入量（含手续费）：Ai = Bi · ((Bo / (Bo - Ao))^(wo / wi) - 1)
+    pub fn calculate_swap_input(
+        output_amount: u64,
+        input_reserve: u64,
+        output_reserve: u64,
+        weight_in_bps: u16,
+        weight_out_bps: u16,
+        fee_bps: u16,
+    ) -> Result<u64> {
+        if output_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 || output_amount >= output_reserve {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+        if output_amount as u128 * 10000 > output_reserve as u128 * MAX_OUT_RATIO_BPS as u128 {
+            return Err(FluxDexError::PriceImpactTooHigh.into());
+        }
+
+        let base = Self::div_up(
+            output_reserve as u128 * FixedPoint::SCALE,
+            (output_reserve - output_amount) as u128,
+        )?;
+        let power = Self::pow_up(base, Self::weight_ratio(weight_out_bps, weight_in_bps)?)?;
+        let ratio = power.saturating_sub(FixedPoint::SCALE);
+
+        // 向上取整
+        let input_after_fee = MathUtils::mul_div(input_reserve as u128, ratio, FixedPoint::SCALE)?
+            .checked_add(1)
+            .ok_or(FluxDexError::Overflow)?;
+
+        let fee_factor = 10000u128
+            .checked_sub(fee_bps as u128)
+            .ok_or(FluxDexError::InvalidCalculation)?;
+        if fee_factor == 0 {
+            return Err(FluxDexError::InvalidFeeTier.into());
+        }
+
+        let input = input_after_fee
+            .checked_mul(10000)
+            .ok_or(FluxDexError::Overflow)?
+            .checked_add(fee_factor - 1)
+            .ok_or(FluxDexError::Overflow)?
+            / fee_factor;
+
+        u64::try_from(input).map_err(|_| FluxDexError::Overflow.into())
+    }
+
+    /// 计算不变量 V = a^wa · b^wb，用作首次存入的 LP 数量
+    pub fn calculate_invariant(
+        amount_a: u64,
+        amount_b: u64,
+        weight_a_bps: u16,
+        weight_b_bps: u16,
+    ) -> Result<u64> {
+        if amount_a == 0 || amount_b == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+
+        let term_a = FixedPoint::from_u64(amount_a).pow(&Self::weight_fraction(weight_a_bps))?;
+        let term_b = FixedPoint::from_u64(amount_b).pow(&Self::weight_fraction(weight_b_bps))?;
+        let invariant = Self::pow_down(term_a.mul(&term_b)?.value)?;
+
+        u64::try_from(invariant / FixedPoint::SCALE).map_err(|_| FluxDexError::Overflow.into())
+    }
+
+    /// 单资产加入：LP = S · ((1 + Ai' / Bi)^wi - 1)
+    ///
+    /// 超出自身权重的部分相当于兑换成另一种代币，按 (1 - wi) · fee 收取手续费。
+    /// 返回 (铸造的 LP 数量, 手续费)
+    pub fn calculate_single_asset_lp(
+        input_amount: u64,
+        input_reserve: u64,
+        weight_in_bps: u16,
+        lp_supply: u64,
+        fee_bps: u16,
+    ) -> Result<(u64, u64)> {
+        if input_amount == 0 {
+            return Err(FluxDexError::InvalidInputAmount.into());
+        }
+        if input_reserve == 0 || lp_supply == 0 {
+            return Err(FluxDexError::InsufficientLiquidity.into());
+        }
+        if input_amount as u128 * 10000 > input_reserve as u128 * MAX_IN_RATIO_BPS as u128 {
+            return Err(FluxDexError::PriceImpactTooHigh.into());
+        }
+
+        // 手续费向上取整
+        let taxable_bps = 10000u128 - weight_in_bps as u128;
+        let fee_amount =
+            (input_amount as u128 * taxable_bps * fee_bps as u128).div_ceil(10000 * 10000) as u64;
+        let input_after_fee = (input_amount - fee_amount) as u128;
+
+        let base =
+            (input_reserve as u128 + input_after_fee) * FixedPoint::SCALE / input_reserve as u128;
+        let power = Self::pow_down(
+            FixedPoint::new(base)
+                .pow(&Self::weight_fraction(weight_in_bps))?
+                .value,
+        )?;
+        let ratio = power.saturating_sub(FixedPoint::SCALE);
+
+        let lp_tokens = MathUtils::mul_div(lp_supply as u128, ratio, FixedPoint::SCALE)?;
+
+        Ok((
+            u64::try_from(lp_tokens).map_err(|_| FluxDexError::Overflow)?,
+            fee_amount,
+        ))
+    }
+
+    /// 权重比 numerator / denominator
+    fn weight_ratio(numerator_bps: u16, denominator_bps: u16) -> Result<FixedPoint> {
+        if denominator_bps == 0 {
+            return Err(FluxDexError::DivisionByZero.into());
+        }
+        Ok(FixedPoint::new(
+            numerator_bps as u128 * FixedPoint::SCALE / denominator_bps as u128,
+        ))
+    }
+
+    /// 权重占比 weight / 10000
+    fn weight_fraction(weight_bps: u16) -> FixedPoint {
+        FixedPoint::new(weight_bps as u128 * FixedPoint::SCALE / 10000)
+    }
+
+    fn pow_up(base: u128, exponent: FixedPoint) -> Result<u128> {
+        let power = FixedPoint::new(base).pow(&exponent)?.value;
+        let error = MathUtils::mul_div(power, Self::MAX_POW_RELATIVE_ERROR, FixedPoint::SCALE)?;
+        power
+            .checked_add(error + 1)
+            .ok_or(FluxDexError::Overflow.into())
+    }
+
+    fn pow_down(power: u128) -> Result<u128> {
+        let error = MathUtils::mul_div(power, Self::MAX_POW_RELATIVE_ERROR, FixedPoint::SCALE)?;
+        Ok(power.saturating_sub(error + 1))
+    }
+
+    fn div_up(numerator: u128, denominator: u128) -> Result<u128> {
+        if denominator == 0 {
+            return Err(FluxDexError::DivisionByZero.into());
+        }
+        Ok(numerator.div_ceil(denominator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_weights_match_constant_product() {
+        let (reserve_in, reserve_out) = (1_000_000_000u64, 2_000_000_000u64);
+        let amount_in = 10_000_000u64;
+
+        let weighted =
+            WeightedMath::calculate_swap_output(amount_in, reserve_in, reserve_out, 5000, 5000, 30)
+                .unwrap();
+        let cp = MathUtils::calculate_amm_output(amount_in, reserve_in, reserve_out, 30).unwrap();
+
+        // 舍入偏向池子，误差极小
+        assert!(weighted <= cp);
+        assert!(cp - weighted < 10);
+
+        let invariant =
+            WeightedMath::calculate_invariant(4_000_000, 9_000_000, 5000, 5000).unwrap();
+        assert!(invariant.abs_diff(6_000_000) <= 1);
+    }
+
+    #[test]
+    fn test_weighted_swap_uses_weights() {
+        // 80/20 池：现价 = (Bo / wo) / (Bi / wi) = 4 · Bo / Bi
+        let reserve = 1_000_000_000_000u64;
+        let amount_in = 1_000_000u64;
+
+        let out = WeightedMath::calculate_swap_output(amount_in, reserve, reserve, 8000, 2000, 0)
+            .unwrap();
+        assert!(out < amount_in * 4);
+        assert!(out > amount_in * 4 - amount_in / 1000);
+
+        // 反向计算需要的输入不少于原输入
+        let required =
+            WeightedMath::calculate_swap_input(out, reserve, reserve, 8000, 2000, 0).unwrap();
+        assert!(required >= amount_in);
+        assert!(required - amount_in < 10);
+
+        // 单笔输入超过上限
+        assert!(
+            WeightedMath::calculate_swap_output(reserve / 2, reserve, reserve, 8000, 2000, 0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_single_asset_join() {
+        let (reserve, lp_supply) = (1_000_000_000u64, 1_000_000_000u64);
+        let amount_in = 10_000_000u64;
+
+        // 无手续费时 LP = S · ((1 + 1%)^0.8 - 1) ≈ 0.7992%
+        let (lp, fee) =
+            WeightedMath::calculate_single_asset_lp(amount_in, reserve, 8000, lp_supply, 0)
+                .unwrap();
+        assert_eq!(fee, 0);
+        assert!(lp > 7_990_000 && lp < 7_993_000);
+
+        // 只对超出自身权重的 20% 收取手续费
+        let (lp_with_fee, fee) =
+            WeightedMath::calculate_single_asset_lp(amount_in, reserve, 8000, lp_supply, 100)
+                .unwrap();
+        assert_eq!(fee, 20_000);
+        assert!(lp_with_fee < lp);
+    }
+}
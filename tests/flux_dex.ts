@@ -1,16 +1,23 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { FluxDex } from "../target/types/flux_dex";
-import { PublicKey, Keypair } from "@solana/web3.js";
+import { PublicKey, Keypair, SystemProgram, Transaction } from "@solana/web3.js";
 import {
   createAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
   createMint,
+  ExtensionType,
   getAccount,
   getAssociatedTokenAddressSync,
+  getMintLen,
   mintTo,
   Token,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
+  transfer,
 } from "@solana/spl-token";
+import { expect } from "chai";
 import { publicKey } from "@coral-xyz/anchor/dist/cjs/utils";
 
 describe("flux_dex", () => {
@@ -19,6 +26,63 @@ describe("flux_dex", () => {
   anchor.setProvider(provider);
   const program = anchor.workspace.fluxDex as Program<FluxDex>;
   const authority = provider.wallet.publicKey;
+  const FEE_TIER_BPS = 30; // 0.3%
+  const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+    "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+  );
+  const metadataPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  // CurveType 的序号，作为 pool 地址种子
+  // 可升级程序的 ProgramData 地址
+  const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+    "BPFLoaderUpgradeab1e11111111111111111111111"
+  );
+  const [programDataPda] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+
+  // 与 MathUtils::calculate_amm_output / calculate_amm_input 一致的报价
+  const bn = (value: anchor.BN) => BigInt(value.toString());
+  const ammOutput = (amountIn: bigint, reserveIn: bigint, reserveOut: bigint, feeBps: number) => {
+    const inputWithFee = amountIn * BigInt(10000 - feeBps);
+    return (inputWithFee * reserveOut) / (reserveIn * 10000n + inputWithFee);
+  };
+  const ammInput = (amountOut: bigint, reserveIn: bigint, reserveOut: bigint, feeBps: number) => {
+    const numerator = reserveIn * amountOut * 10000n;
+    const denominator = (reserveOut - amountOut) * BigInt(10000 - feeBps);
+    return (numerator + denominator - 1n) / denominator;
+  };
+  const SWAP_AMOUNT = 10_000n;
+
+  const CURVE_CONSTANT_PRODUCT = 0;
+  const CURVE_STABLE_SWAP = 1;
+  const CURVE_CONCENTRATED = 3;
+
+  // pool 地址由交易对、费率档位和定价曲线派生
+  const derivePoolPda = (
+    mintA: PublicKey,
+    mintB: PublicKey,
+    feeTierBps: number,
+    curveType: number
+  ) => {
+    const feeTier = Buffer.alloc(2);
+    feeTier.writeUInt16LE(feeTierBps);
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pool"),
+        mintA.toBuffer(),
+        mintB.toBuffer(),
+        feeTier,
+        Buffer.from([curveType]),
+      ],
+      program.programId
+    )[0];
+  };
 
   let tokenAMint: PublicKey;
   let tokenBMint: PublicKey;
@@ -29,6 +93,136 @@ describe("flux_dex", () => {
   let userTokenA: PublicKey;
   let userTokenB: PublicKey;
   let userLpToken: PublicKey;
+  // user_lp_token 以 init_if_needed 创建，首次存入时需要账户签名
+  const userLpKeypair = Keypair.generate();
+
+  // 断言交易失败，且错误信息匹配 pattern
+  const expectError = async (promise: Promise<unknown>, pattern: RegExp) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect(String(err) + JSON.stringify(err)).to.match(pattern);
+      return;
+    }
+    expect.fail(`expected error matching ${pattern}`);
+  };
+
+  // 一个代币及其所属的代币程序
+  type TokenInfo = { mint: PublicKey; program: PublicKey };
+  type PoolInfo = {
+    pool: PublicKey;
+    lpMint: PublicKey;
+    vaultA: PublicKey;
+    vaultB: PublicKey;
+    tokenA: TokenInfo;
+    tokenB: TokenInfo;
+  };
+
+  // 按排序后的交易对创建恒定乘积池
+  // 默认创建恒定乘积池；传入 concentrated 时创建集中流动性池
+  const createPool = async (
+    x: TokenInfo,
+    y: TokenInfo,
+    concentrated?: { tickSpacing: number; sqrtPriceX64: anchor.BN }
+  ): Promise<PoolInfo> => {
+    const [tokenA, tokenB] =
+      Buffer.compare(x.mint.toBuffer(), y.mint.toBuffer()) < 0 ? [x, y] : [y, x];
+    const pool = derivePoolPda(
+      tokenA.mint,
+      tokenB.mint,
+      FEE_TIER_BPS,
+      concentrated ? CURVE_CONCENTRATED : CURVE_CONSTANT_PRODUCT
+    );
+    const [lpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), pool.toBuffer()],
+      program.programId
+    );
+    const [vaultA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_vault_a"), pool.toBuffer()],
+      program.programId
+    );
+    const [vaultB] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_vault_b"), pool.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializePool(
+        FEE_TIER_BPS,
+        concentrated ? { concentrated: {} } : { constantProduct: {} },
+        new anchor.BN(0),
+        5000,
+        concentrated ? concentrated.tickSpacing : 0,
+        concentrated ? concentrated.sqrtPriceX64 : new anchor.BN(0)
+      )
+      .accounts({
+        authority: authority,
+        pool: pool,
+        tokenAMint: tokenA.mint,
+        tokenBMint: tokenB.mint,
+        lpMint: lpMint,
+        lpMetadata: metadataPda(lpMint),
+        tokenAMetadata: metadataPda(tokenA.mint),
+        tokenBMetadata: metadataPda(tokenB.mint),
+        tokenAProgram: tokenA.program,
+        tokenBProgram: tokenB.program,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+    return { pool, lpMint, vaultA, vaultB, tokenA, tokenB };
+  };
+
+  const positionPdaFor = (pool: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), authority.toBuffer(), pool.toBuffer()],
+      program.programId
+    )[0];
+
+  // 钱包在 mint 上的关联代币账户
+  const walletAta = (token: TokenInfo) =>
+    getAssociatedTokenAddressSync(token.mint, authority, false, token.program);
+
+  // 钱包向池子存入流动性，返回新建的 LP 账户
+  const addPoolLiquidity = async (info: PoolInfo, amountA: number, amountB: number) => {
+    const lpKeypair = Keypair.generate();
+    await program.methods
+      .addLiquidity(
+        new anchor.BN(amountA),
+        new anchor.BN(amountB),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        user: authority,
+        pool: info.pool,
+        userTokenA: walletAta(info.tokenA),
+        userTokenB: walletAta(info.tokenB),
+        userLpToken: lpKeypair.publicKey,
+        poolTokenAVault: info.vaultA,
+        poolTokenBVault: info.vaultB,
+        lpMint: info.lpMint,
+        position: positionPdaFor(info.pool),
+        tokenAMint: info.tokenA.mint,
+        tokenBMint: info.tokenB.mint,
+        tokenAProgram: info.tokenA.program,
+        tokenBProgram: info.tokenB.program,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lpKeypair])
+      .rpc();
+    return lpKeypair.publicKey;
+  };
+
+  // 创建 mint 并给钱包的关联代币账户铸造 amount
+  const createFundedMint = async (amount: number): Promise<TokenInfo> => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const mint = await createMint(provider.connection, payer, authority, null, 6);
+    const ata = await createAssociatedTokenAccount(provider.connection, payer, mint, authority);
+    await mintTo(provider.connection, payer, mint, ata, authority, amount);
+    return { mint, program: TOKEN_PROGRAM_ID };
+  };
 
   before(async () => {
     // create mint
@@ -50,10 +244,12 @@ describe("flux_dex", () => {
     console.log("✅ Token A Mint:", tokenAMint.toBase58());
     console.log("✅ Token B Mint:", tokenBMint.toBase58());
 
-    [poolPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("pool"), authority.toBuffer()],
-      program.programId
-    );
+    // pool 地址要求 token_a_mint < token_b_mint
+    if (Buffer.compare(tokenAMint.toBuffer(), tokenBMint.toBuffer()) > 0) {
+      [tokenAMint, tokenBMint] = [tokenBMint, tokenAMint];
+    }
+
+    poolPda = derivePoolPda(tokenAMint, tokenBMint, FEE_TIER_BPS, CURVE_CONSTANT_PRODUCT);
 
     // derive lp_mint PDA
     [lpMintPda] = PublicKey.findProgramAddressSync(
@@ -83,7 +279,7 @@ describe("flux_dex", () => {
       tokenBMint,
       authority
     );
-    userLpToken = await getAssociatedTokenAddressSync(lpMintPda, authority);
+    userLpToken = userLpKeypair.publicKey;
 
     // 给用户mint 初始代币
     await mintTo(
@@ -107,10 +303,55 @@ describe("flux_dex", () => {
     console.log("✅ poolpda address:",poolPda.toBase58())
   });
 
+  it("Initialize global config", async () => {
+    const [globalConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global_config")],
+      program.programId
+    );
+
+    // 只有程序升级权限（测试中为钱包）能创建全局配置
+    const intruder = Keypair.generate();
+    await expectError(
+      program.methods
+        .initializeGlobalConfig(1000, intruder.publicKey)
+        .accounts({
+          superAdmin: intruder.publicKey,
+          programData: programDataPda,
+        })
+        .signers([intruder])
+        .rpc(),
+      /Unauthorized/
+    );
+
+    const tx = await program.methods
+      .initializeGlobalConfig(
+        1000, // 10% of swap fees go to the protocol
+        authority // treasury
+      )
+      .accounts({
+        superAdmin: authority,
+        programData: programDataPda,
+        globalConfig: globalConfigPda,
+      })
+      .rpc();
+
+    const config = await program.account.globalConfig.fetch(globalConfigPda);
+    expect(config.almEnabledGlobally).to.equal(false);
+    expect(config.mevProtectionEnabledGlobally).to.equal(false);
+    console.log("✅ Global config initialized, tx:", tx);
+  });
+
   it("Is initialized!", async () => {
     // Add your test here.
     const tx = await program.methods
-      .initializePool(0.3)
+      .initializePool(
+        FEE_TIER_BPS,
+        { constantProduct: {} },
+        new anchor.BN(0),
+        5000,
+        0,
+        new anchor.BN(0)
+      )
       .accounts({
         authority: authority,
         pool: poolPda,
@@ -119,12 +360,153 @@ describe("flux_dex", () => {
         tokenAVault: tokenAVault,
         tokenBVault: tokenBVault,
         lpMint: lpMintPda,
+        lpMetadata: metadataPda(lpMintPda),
+        tokenAMetadata: metadataPda(tokenAMint),
+        tokenBMetadata: metadataPda(tokenBMint),
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
       })
       .rpc();
     console.log("Your transaction signature", tx);
   });
 
+  it("Initialize pool rejects unsorted mints", async () => {
+    // 同一交易对只允许按 token_a_mint < token_b_mint 的顺序建池
+    const reversedPool = derivePoolPda(tokenBMint, tokenAMint, FEE_TIER_BPS, CURVE_CONSTANT_PRODUCT);
+    const [reversedLpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), reversedPool.toBuffer()],
+      program.programId
+    );
+    await expectError(
+      program.methods
+        .initializePool(
+          FEE_TIER_BPS,
+          { constantProduct: {} },
+          new anchor.BN(0),
+          5000,
+          0,
+          new anchor.BN(0)
+        )
+        .accounts({
+          authority: authority,
+          pool: reversedPool,
+          tokenAMint: tokenBMint,
+          tokenBMint: tokenAMint,
+          lpMint: reversedLpMint,
+          lpMetadata: metadataPda(reversedLpMint),
+          tokenAMetadata: metadataPda(tokenBMint),
+          tokenBMetadata: metadataPda(tokenAMint),
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .rpc(),
+      /InvalidAccount/
+    );
+    expect(await provider.connection.getAccountInfo(reversedPool)).to.equal(null);
+
+    // 规范地址上的池子记录的是排序后的交易对
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.tokenAMint.toBase58()).to.equal(tokenAMint.toBase58());
+    expect(pool.tokenBMint.toBase58()).to.equal(tokenBMint.toBase58());
+    expect(pool.feeTier).to.equal(FEE_TIER_BPS);
+  });
+
+  it("Pools with different curves share a pair and fee tier", async () => {
+    const stablePool = derivePoolPda(tokenAMint, tokenBMint, FEE_TIER_BPS, CURVE_STABLE_SWAP);
+    const [stableLpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), stablePool.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializePool(
+        FEE_TIER_BPS,
+        { stableSwap: {} },
+        new anchor.BN(100),
+        5000,
+        0,
+        new anchor.BN(0)
+      )
+      .accounts({
+        authority: authority,
+        pool: stablePool,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        lpMint: stableLpMint,
+        lpMetadata: metadataPda(stableLpMint),
+        tokenAMetadata: metadataPda(tokenAMint),
+        tokenBMetadata: metadataPda(tokenBMint),
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+
+    const stable = await program.account.pool.fetch(stablePool);
+    const constantProduct = await program.account.pool.fetch(poolPda);
+    expect(stable.curveType).to.deep.equal({ stableSwap: {} });
+    expect(constantProduct.curveType).to.deep.equal({ constantProduct: {} });
+  });
+
+  it("StableSwap pools require matching decimals", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const nineDecimals = await createMint(provider.connection, payer, authority, null, 9);
+    const [mintA, mintB] =
+      Buffer.compare(tokenAMint.toBuffer(), nineDecimals.toBuffer()) < 0
+        ? [tokenAMint, nineDecimals]
+        : [nineDecimals, tokenAMint];
+    const pool = derivePoolPda(mintA, mintB, FEE_TIER_BPS, CURVE_STABLE_SWAP);
+    const [lpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), pool.toBuffer()],
+      program.programId
+    );
+
+    await expectError(
+      program.methods
+        .initializePool(
+          FEE_TIER_BPS,
+          { stableSwap: {} },
+          new anchor.BN(100),
+          5000,
+          0,
+          new anchor.BN(0)
+        )
+        .accounts({
+          authority: authority,
+          pool: pool,
+          tokenAMint: mintA,
+          tokenBMint: mintB,
+          lpMint: lpMint,
+          lpMetadata: metadataPda(lpMint),
+          tokenAMetadata: metadataPda(mintA),
+          tokenBMetadata: metadataPda(mintB),
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .rpc(),
+      /MintDecimalsMismatch/
+    );
+  });
+
+  it("Update LP metadata URI", async () => {
+    const tx = await program.methods
+      .updateLpMetadataUri("https://example.com/flux-lp.json")
+      .accounts({
+        authority: authority,
+        pool: poolPda,
+        lpMetadata: metadataPda(lpMintPda),
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+    console.log("✅ LP metadata URI updated, tx:", tx);
+  });
+
   it("Add liquidity", async () => {
 
     const [positionPda] = PublicKey.findProgramAddressSync(
@@ -149,9 +531,14 @@ describe("flux_dex", () => {
         poolTokenAVault: tokenAVault,
         poolTokenBVault: tokenBVault,
         lpMint: lpMintPda,
-        postion: positionPda,
+        position: positionPda,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .signers([userLpKeypair])
       .rpc();
 
     console.log("✅ Liquidity added, tx:", tx);
@@ -165,4 +552,1172 @@ describe("flux_dex", () => {
     console.log("User B balance:", Number(userBAcc.amount));
     console.log("User LP balance:", Number(userLpAcc.amount));
   });
+
+  it("First deposit locks minimum liquidity", async () => {
+    const MIN_LIQUIDITY = 1000;
+    const [lockedLpAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("locked_lp_authority"), poolPda.toBuffer()],
+      program.programId
+    );
+    const [lockedLpToken] = PublicKey.findProgramAddressSync(
+      [Buffer.from("locked_lp"), poolPda.toBuffer()],
+      program.programId
+    );
+
+    const lockedAcc = await getAccount(provider.connection, lockedLpToken);
+    expect(lockedAcc.owner.toBase58()).to.equal(lockedLpAuthority.toBase58());
+    expect(Number(lockedAcc.amount)).to.equal(MIN_LIQUIDITY);
+
+    // 总供应量 = 用户持有 + 永久锁定
+    const userLpAcc = await getAccount(provider.connection, userLpToken);
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.lpSupply.toNumber()).to.equal(Number(userLpAcc.amount) + MIN_LIQUIDITY);
+  });
+
+  it("LP tokens cannot be transferred", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const lpAcc = await getAccount(provider.connection, userLpToken);
+    expect(lpAcc.isFrozen).to.equal(true);
+
+    const receiver = Keypair.generate();
+    const receiverLpToken = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      lpMintPda,
+      receiver.publicKey
+    );
+
+    await expectError(
+      transfer(provider.connection, payer, userLpToken, receiverLpToken, payer, 1),
+      /frozen|0x11/
+    );
+    const receiverAcc = await getAccount(provider.connection, receiverLpToken);
+    expect(Number(receiverAcc.amount)).to.equal(0);
+  });
+
+  it("Close position rejects a position that still holds LP", async () => {
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), authority.toBuffer(), poolPda.toBuffer()],
+      program.programId
+    );
+
+    await expectError(
+      program.methods
+        .closePosition()
+        .accounts({
+          owner: authority,
+          pool: poolPda,
+          position: positionPda,
+        })
+        .rpc(),
+      /PositionNotEmpty/
+    );
+    const position = await program.account.position.fetch(positionPda);
+    expect(position.lpTokens.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("Swap A -> B", async () => {
+    const swapAToB = (minOut: bigint) =>
+      program.methods
+        .swap(
+          new anchor.BN(SWAP_AMOUNT.toString()), // amount in
+          new anchor.BN(minOut.toString()), // min out
+          true // A -> B
+        )
+        .accounts({
+          user: authority,
+          pool: poolPda,
+          userTokenA: userTokenA,
+          userTokenB: userTokenB,
+          poolTokenAVault: tokenAVault,
+          poolTokenBVault: tokenBVault,
+          tokenAMint: tokenAMint,
+          tokenBMint: tokenBMint,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const poolBefore = await program.account.pool.fetch(poolPda);
+    const quote = ammOutput(
+      SWAP_AMOUNT,
+      bn(poolBefore.tokenAReserve),
+      bn(poolBefore.tokenBReserve),
+      poolBefore.currentFeeBps
+    );
+
+    // 最少输出高于报价时拒绝
+    await expectError(swapAToB(quote + 1n), /MinimumOutputNotMet/);
+
+    const beforeB = await getAccount(provider.connection, userTokenB);
+    const tx = await swapAToB(quote);
+    console.log("✅ Swap executed, tx:", tx);
+
+    const afterB = await getAccount(provider.connection, userTokenB);
+    expect(afterB.amount - beforeB.amount).to.equal(quote);
+
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.swapCount.toNumber()).to.equal(poolBefore.swapCount.toNumber() + 1);
+    expect(bn(pool.tokenBReserve)).to.equal(bn(poolBefore.tokenBReserve) - quote);
+  });
+
+  it("Swap exact out B -> A", async () => {
+    const amountOut = 5_000n;
+    const swapExactOut = (maxIn: bigint) =>
+      program.methods
+        .swapExactOut(
+          new anchor.BN(amountOut.toString()),
+          new anchor.BN(maxIn.toString()),
+          false // B -> A
+        )
+        .accounts({
+          user: authority,
+          pool: poolPda,
+          userTokenA: userTokenA,
+          userTokenB: userTokenB,
+          poolTokenAVault: tokenAVault,
+          poolTokenBVault: tokenBVault,
+          tokenAMint: tokenAMint,
+          tokenBMint: tokenBMint,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const poolBefore = await program.account.pool.fetch(poolPda);
+    const quote = ammInput(
+      amountOut,
+      bn(poolBefore.tokenBReserve),
+      bn(poolBefore.tokenAReserve),
+      poolBefore.currentFeeBps
+    );
+
+    // 最多输入低于报价时拒绝
+    await expectError(swapExactOut(quote - 1n), /SlippageExceeded/);
+
+    const beforeA = await getAccount(provider.connection, userTokenA);
+    const beforeB = await getAccount(provider.connection, userTokenB);
+    await swapExactOut(quote);
+    const afterA = await getAccount(provider.connection, userTokenA);
+    const afterB = await getAccount(provider.connection, userTokenB);
+
+    expect(afterA.amount - beforeA.amount).to.equal(amountOut);
+    expect(beforeB.amount - afterB.amount).to.equal(quote);
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.swapCount.toNumber()).to.equal(poolBefore.swapCount.toNumber() + 1);
+  });
+
+  it("Skim sends donated surplus to the recipient", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const recipient = Keypair.generate();
+    const recipientTokenA = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      tokenAMint,
+      recipient.publicKey
+    );
+    const recipientTokenB = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      tokenBMint,
+      recipient.publicKey
+    );
+
+    // 直接向金库转账，不经过池子记账
+    await transfer(provider.connection, payer, userTokenA, tokenAVault, authority, 7_000);
+    await transfer(provider.connection, payer, userTokenB, tokenBVault, authority, 3_000);
+    const poolBefore = await program.account.pool.fetch(poolPda);
+
+    await program.methods
+      .skim()
+      .accounts({
+        pool: poolPda,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+        recipientTokenA: recipientTokenA,
+        recipientTokenB: recipientTokenB,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const recipientA = await getAccount(provider.connection, recipientTokenA);
+    const recipientB = await getAccount(provider.connection, recipientTokenB);
+    expect(Number(recipientA.amount)).to.equal(7_000);
+    expect(Number(recipientB.amount)).to.equal(3_000);
+
+    // 金库余额回到 储备 + 协议费 + 未领取的 LP 手续费
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.tokenAReserve.eq(poolBefore.tokenAReserve)).to.equal(true);
+    expect(pool.tokenBReserve.eq(poolBefore.tokenBReserve)).to.equal(true);
+    const vaultA = await getAccount(provider.connection, tokenAVault);
+    const vaultB = await getAccount(provider.connection, tokenBVault);
+    expect(Number(vaultA.amount)).to.equal(
+      pool.tokenAReserve.add(pool.protocolFeesA).add(pool.unclaimedLpFeesA).toNumber()
+    );
+    expect(Number(vaultB.amount)).to.equal(
+      pool.tokenBReserve.add(pool.protocolFeesB).add(pool.unclaimedLpFeesB).toNumber()
+    );
+  });
+
+  it("Sync folds donations into reserves but not fee balances", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const poolBefore = await program.account.pool.fetch(poolPda);
+    // 前面的兑换已经产生了协议费和 LP 手续费
+    expect(poolBefore.protocolFeesA.toNumber()).to.be.greaterThan(0);
+    expect(poolBefore.unclaimedLpFeesA.toNumber()).to.be.greaterThan(0);
+
+    await transfer(provider.connection, payer, userTokenA, tokenAVault, authority, 5_000);
+
+    await program.methods
+      .sync()
+      .accounts({
+        pool: poolPda,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+      })
+      .rpc();
+
+    const pool = await program.account.pool.fetch(poolPda);
+    const vaultA = await getAccount(provider.connection, tokenAVault);
+    const vaultB = await getAccount(provider.connection, tokenBVault);
+    expect(pool.tokenAReserve.toNumber()).to.equal(
+      poolBefore.tokenAReserve.toNumber() + 5_000
+    );
+    expect(pool.tokenAReserve.toNumber()).to.equal(
+      Number(vaultA.amount) - pool.protocolFeesA.toNumber() - pool.unclaimedLpFeesA.toNumber()
+    );
+    expect(pool.tokenBReserve.toNumber()).to.equal(
+      Number(vaultB.amount) - pool.protocolFeesB.toNumber() - pool.unclaimedLpFeesB.toNumber()
+    );
+    // 手续费余额不被并入储备
+    expect(pool.protocolFeesA.eq(poolBefore.protocolFeesA)).to.equal(true);
+    expect(pool.unclaimedLpFeesA.eq(poolBefore.unclaimedLpFeesA)).to.equal(true);
+  });
+
+  it("Collect fees pays the position's accrued LP fees", async () => {
+    const positionBefore = await program.account.position.fetch(positionPda);
+    const beforeA = await getAccount(provider.connection, userTokenA);
+    const beforeB = await getAccount(provider.connection, userTokenB);
+
+    await program.methods
+      .collectFees()
+      .accounts({
+        user: authority,
+        pool: poolPda,
+        userTokenA: userTokenA,
+        userTokenB: userTokenB,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+        position: positionPda,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const afterA = await getAccount(provider.connection, userTokenA);
+    const afterB = await getAccount(provider.connection, userTokenB);
+    const position = await program.account.position.fetch(positionPda);
+    const paidA = bn(position.realizedFeesA) - bn(positionBefore.realizedFeesA);
+    const paidB = bn(position.realizedFeesB) - bn(positionBefore.realizedFeesB);
+
+    // 两个方向的兑换都为 LP 产生了手续费
+    expect(paidA > 0n).to.equal(true);
+    expect(paidB > 0n).to.equal(true);
+    expect(afterA.amount - beforeA.amount).to.equal(paidA);
+    expect(afterB.amount - beforeB.amount).to.equal(paidB);
+    expect(position.feesOwedA.toNumber()).to.equal(0);
+    expect(position.feesOwedB.toNumber()).to.equal(0);
+  });
+
+  it("Collect protocol fees sends them to the treasury", async () => {
+    const [globalConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global_config")],
+      program.programId
+    );
+    // 国库即钱包，协议费转入钱包的代币账户
+    const collect = (superAdmin: Keypair | null) =>
+      program.methods
+        .collectProtocolFees()
+        .accounts({
+          superAdmin: superAdmin ? superAdmin.publicKey : authority,
+          pool: poolPda,
+          poolTokenAVault: tokenAVault,
+          poolTokenBVault: tokenBVault,
+          treasuryTokenA: userTokenA,
+          treasuryTokenB: userTokenB,
+          tokenAMint: tokenAMint,
+          tokenBMint: tokenBMint,
+          tokenAProgram: TOKEN_PROGRAM_ID,
+          tokenBProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(superAdmin ? [superAdmin] : [])
+        .rpc();
+
+    await expectError(collect(Keypair.generate()), /Unauthorized/);
+
+    const poolBefore = await program.account.pool.fetch(poolPda);
+    const configBefore = await program.account.globalConfig.fetch(globalConfigPda);
+    const owedA = bn(poolBefore.protocolFeesA);
+    const owedB = bn(poolBefore.protocolFeesB);
+    expect(owedA > 0n).to.equal(true);
+    expect(owedB > 0n).to.equal(true);
+
+    const beforeA = await getAccount(provider.connection, userTokenA);
+    const beforeB = await getAccount(provider.connection, userTokenB);
+    await collect(null);
+    const afterA = await getAccount(provider.connection, userTokenA);
+    const afterB = await getAccount(provider.connection, userTokenB);
+
+    expect(afterA.amount - beforeA.amount).to.equal(owedA);
+    expect(afterB.amount - beforeB.amount).to.equal(owedB);
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.protocolFeesA.toNumber()).to.equal(0);
+    expect(pool.protocolFeesB.toNumber()).to.equal(0);
+    const config = await program.account.globalConfig.fetch(globalConfigPda);
+    expect(bn(config.totalFeesCollected)).to.equal(
+      bn(configBefore.totalFeesCollected) + owedA + owedB
+    );
+
+    // 已全部提取，再次提取被拒绝
+    await expectError(collect(null), /InsufficientFeeBalance/);
+  });
+
+  it("Dynamic fee adjusts after a swap once enabled", async () => {
+    await program.methods
+      .setPoolFlag({ dynamicFee: {} }, true)
+      .accounts({
+        authority: authority,
+        pool: poolPda,
+      })
+      .rpc();
+
+    await program.methods
+      .swap(new anchor.BN(5_000), new anchor.BN(1), false) // B -> A
+      .accounts({
+        user: authority,
+        pool: poolPda,
+        userTokenA: userTokenA,
+        userTokenB: userTokenB,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // 池子很浅且价格有变动，费率高于基础费率
+    let pool = await program.account.pool.fetch(poolPda);
+    expect(pool.dynamicFeeEnabled).to.equal(true);
+    expect(pool.volatilityScore).to.be.greaterThan(0);
+    expect(pool.currentFeeBps).to.be.greaterThan(pool.baseFeeBps);
+
+    // 关闭后回到基础费率
+    await program.methods
+      .setPoolFlag({ dynamicFee: {} }, false)
+      .accounts({
+        authority: authority,
+        pool: poolPda,
+      })
+      .rpc();
+    pool = await program.account.pool.fetch(poolPda);
+    expect(pool.currentFeeBps).to.equal(FEE_TIER_BPS);
+  });
+
+  it("Remove liquidity", async () => {
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), authority.toBuffer(), poolPda.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .removeLiquidity(
+        new anchor.BN(10_000), // LP amount
+        new anchor.BN(0), // min A
+        new anchor.BN(0) // min B
+      )
+      .accounts({
+        user: authority,
+        pool: poolPda,
+        userTokenA: userTokenA,
+        userTokenB: userTokenB,
+        userLpToken: userLpToken,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+        lpMint: lpMintPda,
+        position: positionPda,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    console.log("✅ Liquidity removed, tx:", tx);
+
+    // 赎回后 LP 账户重新冻结，Position 与钱包余额一致
+    const position = await program.account.position.fetch(positionPda);
+    const lpAcc = await getAccount(provider.connection, userLpToken);
+    console.log("Position LP tokens:", position.lpTokens.toNumber());
+    expect(lpAcc.isFrozen).to.equal(true);
+    expect(position.lpTokens.toNumber()).to.equal(Number(lpAcc.amount));
+  });
+
+  it("Remove liquidity into a single token", async () => {
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), authority.toBuffer(), poolPda.toBuffer()],
+      program.programId
+    );
+    const positionBefore = await program.account.position.fetch(positionPda);
+    const beforeA = await getAccount(provider.connection, userTokenA);
+    const beforeB = await getAccount(provider.connection, userTokenB);
+
+    const tx = await program.methods
+      .removeLiquiditySingleSided(
+        new anchor.BN(5_000), // LP amount
+        new anchor.BN(1) // min out
+      )
+      .accounts({
+        user: authority,
+        pool: poolPda,
+        userTokenOut: userTokenA,
+        userLpToken: userLpToken,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+        lpMint: lpMintPda,
+        position: positionPda,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    console.log("✅ Single-sided liquidity removed, tx:", tx);
+
+    // 全部以 Token A 取回，Token B 余额不变
+    const afterA = await getAccount(provider.connection, userTokenA);
+    const afterB = await getAccount(provider.connection, userTokenB);
+    expect(Number(afterA.amount - beforeA.amount)).to.be.greaterThan(0);
+    expect(Number(afterB.amount)).to.equal(Number(beforeB.amount));
+
+    const positionAfter = await program.account.position.fetch(positionPda);
+    expect(
+      positionBefore.lpTokens.toNumber() - positionAfter.lpTokens.toNumber()
+    ).to.equal(5_000);
+  });
+
+  it("Emergency withdraw", async () => {
+    const [positionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), authority.toBuffer(), poolPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .setPoolFlag({ emergencyMode: {} }, true)
+      .accounts({
+        authority: authority,
+        pool: poolPda,
+      })
+      .rpc();
+
+    const tx = await program.methods
+      .emergencyWithdraw(new anchor.BN(10_000)) // LP amount
+      .accounts({
+        user: authority,
+        pool: poolPda,
+        userTokenA: userTokenA,
+        userTokenB: userTokenB,
+        userLpToken: userLpToken,
+        poolTokenAVault: tokenAVault,
+        poolTokenBVault: tokenBVault,
+        lpMint: lpMintPda,
+        position: positionPda,
+        tokenAMint: tokenAMint,
+        tokenBMint: tokenBMint,
+        tokenAProgram: TOKEN_PROGRAM_ID,
+        tokenBProgram: TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    console.log("✅ Emergency withdraw, tx:", tx);
+
+    const position = await program.account.position.fetch(positionPda);
+    console.log("Position LP tokens:", position.lpTokens.toNumber());
+  });
+
+  it("Non-authority cannot change pool settings", async () => {
+    const intruder = Keypair.generate();
+    await expectError(
+      program.methods
+        .setPoolFlag({ paused: {} }, true)
+        .accounts({ authority: intruder.publicKey, pool: poolPda })
+        .signers([intruder])
+        .rpc(),
+      /Unauthorized/
+    );
+    await expectError(
+      program.methods
+        .updateBaseFee(100)
+        .accounts({ authority: intruder.publicKey, pool: poolPda })
+        .signers([intruder])
+        .rpc(),
+      /Unauthorized/
+    );
+  });
+
+  it("Emergency mode can be turned off while active", async () => {
+    // 紧急模式下其他开关被拒绝
+    await expectError(
+      program.methods
+        .setPoolFlag({ paused: {} }, true)
+        .accounts({ authority: authority, pool: poolPda })
+        .rpc(),
+      /EmergencyModeActive/
+    );
+
+    await program.methods
+      .setPoolFlag({ emergencyMode: {} }, false)
+      .accounts({ authority: authority, pool: poolPda })
+      .rpc();
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.emergencyMode).to.equal(false);
+    expect(pool.paused).to.equal(false);
+  });
+
+  it("Authority transfer requires the pending holder to accept", async () => {
+    const newAuthority = Keypair.generate();
+    const intruder = Keypair.generate();
+
+    await program.methods
+      .proposeAuthority({ authority: {} }, newAuthority.publicKey)
+      .accounts({ signer: authority, pool: poolPda })
+      .rpc();
+
+    await expectError(
+      program.methods
+        .acceptAuthority({ authority: {} })
+        .accounts({ signer: intruder.publicKey, pool: poolPda })
+        .signers([intruder])
+        .rpc(),
+      /Unauthorized/
+    );
+
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.authority.toBase58()).to.equal(authority.toBase58());
+    expect(pool.pendingAuthority.toBase58()).to.equal(
+      newAuthority.publicKey.toBase58()
+    );
+  });
+
+  it("Cancel clears the pending authority", async () => {
+    await program.methods
+      .cancelAuthorityTransfer({ authority: {} })
+      .accounts({ signer: authority, pool: poolPda })
+      .rpc();
+
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.pendingAuthority.toBase58()).to.equal(
+      PublicKey.default.toBase58()
+    );
+
+    // 取消后无可接受的移交
+    await expectError(
+      program.methods
+        .acceptAuthority({ authority: {} })
+        .accounts({ signer: authority, pool: poolPda })
+        .rpc(),
+      /NoPendingAuthority/
+    );
+  });
+
+  describe("Flash loan", () => {
+    // SPL Token 程序本身作为回调程序：回调即一笔 Transfer，把借款连同手续费转回金库
+    let info: PoolInfo;
+    const BORROW = 100_000;
+    const FEE = Math.ceil((BORROW * FEE_TIER_BPS) / 10000);
+
+    // SPL Token Transfer 指令数据：tag 3 + u64 数量
+    const transferData = (amount: number) => {
+      const data = Buffer.alloc(9);
+      data.writeUInt8(3, 0);
+      data.writeBigUInt64LE(BigInt(amount), 1);
+      return data;
+    };
+
+    const flashLoan = (repay: number, receiverProgram: PublicKey = TOKEN_PROGRAM_ID) =>
+      program.methods
+        .flashLoan(new anchor.BN(BORROW), new anchor.BN(0), transferData(repay))
+        .accounts({
+          borrower: authority,
+          pool: info.pool,
+          destinationTokenA: walletAta(info.tokenA),
+          destinationTokenB: walletAta(info.tokenB),
+          poolTokenAVault: info.vaultA,
+          poolTokenBVault: info.vaultB,
+          receiverProgram: receiverProgram,
+          tokenAMint: info.tokenA.mint,
+          tokenBMint: info.tokenB.mint,
+          tokenAProgram: info.tokenA.program,
+          tokenBProgram: info.tokenB.program,
+        })
+        .remainingAccounts([
+          { pubkey: walletAta(info.tokenA), isSigner: false, isWritable: true },
+          { pubkey: info.vaultA, isSigner: false, isWritable: true },
+          { pubkey: authority, isSigner: true, isWritable: false },
+        ])
+        .rpc();
+
+    before(async () => {
+      info = await createPool(
+        await createFundedMint(1_000_000_000),
+        await createFundedMint(1_000_000_000)
+      );
+      await addPoolLiquidity(info, 1_000_000, 1_000_000);
+    });
+
+    it("Repaid loan credits the fee to the reserves", async () => {
+      const poolBefore = await program.account.pool.fetch(info.pool);
+      const vaultBefore = await getAccount(provider.connection, info.vaultA);
+
+      await flashLoan(BORROW + FEE);
+
+      const pool = await program.account.pool.fetch(info.pool);
+      const vault = await getAccount(provider.connection, info.vaultA);
+      expect(Number(vault.amount)).to.equal(Number(vaultBefore.amount) + FEE);
+      expect(pool.tokenAReserve.toNumber()).to.equal(poolBefore.tokenAReserve.toNumber() + FEE);
+      expect(pool.tokenBReserve.eq(poolBefore.tokenBReserve)).to.equal(true);
+      expect(pool.flashLoanCount.toNumber()).to.equal(poolBefore.flashLoanCount.toNumber() + 1);
+      expect(pool.locked).to.equal(false);
+    });
+
+    it("Loan repaid without the fee is rejected", async () => {
+      await expectError(flashLoan(BORROW), /FlashLoanNotRepaid/);
+      await expectError(flashLoan(BORROW + FEE - 1), /FlashLoanNotRepaid/);
+    });
+
+    it("The pool program itself cannot be the receiver", async () => {
+      // 运行时只允许程序直接自调用重入，因此回调程序不能是本程序
+      await expectError(flashLoan(BORROW + FEE, program.programId), /InvalidAccount/);
+    });
+  });
+
+  describe("Route swap", () => {
+    let tokenX: TokenInfo;
+    let tokenY: TokenInfo;
+    let tokenZ: TokenInfo;
+    let poolXY: PoolInfo;
+    let poolYZ: PoolInfo;
+
+    const oraclePda = (pool: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("observation"), pool.toBuffer()],
+        program.programId
+      )[0];
+
+    // 一跳占用的 remaining_accounts：pool, vault_in, vault_out, mint_out, token_program_out, price_oracle
+    const hop = (info: PoolInfo, mintIn: PublicKey, overrides: { oracle?: PublicKey } = {}) => {
+      const aToB = info.tokenA.mint.equals(mintIn);
+      const tokenOut = aToB ? info.tokenB : info.tokenA;
+      return [
+        { pubkey: info.pool, isSigner: false, isWritable: true },
+        { pubkey: aToB ? info.vaultA : info.vaultB, isSigner: false, isWritable: true },
+        { pubkey: aToB ? info.vaultB : info.vaultA, isSigner: false, isWritable: true },
+        { pubkey: tokenOut.mint, isSigner: false, isWritable: false },
+        { pubkey: tokenOut.program, isSigner: false, isWritable: false },
+        {
+          pubkey: overrides.oracle ?? oraclePda(info.pool),
+          isSigner: false,
+          isWritable: true,
+        },
+      ];
+    };
+
+    const routeSwap = (
+      tokenIn: TokenInfo,
+      tokenOut: TokenInfo,
+      hops: { pubkey: PublicKey; isSigner: boolean; isWritable: boolean }[],
+      minAmountOut: anchor.BN = new anchor.BN(1)
+    ) =>
+      program.methods
+        .routeSwap(new anchor.BN(10_000), minAmountOut)
+        .accounts({
+          user: authority,
+          userTokenIn: walletAta(tokenIn),
+          userTokenOut: walletAta(tokenOut),
+          tokenInMint: tokenIn.mint,
+          tokenInProgram: tokenIn.program,
+        })
+        .remainingAccounts(hops)
+        .rpc();
+
+    before(async () => {
+      tokenX = await createFundedMint(1_000_000_000);
+      tokenY = await createFundedMint(1_000_000_000);
+      tokenZ = await createFundedMint(1_000_000_000);
+      poolXY = await createPool(tokenX, tokenY);
+      poolYZ = await createPool(tokenY, tokenZ);
+      await addPoolLiquidity(poolXY, 1_000_000, 1_000_000);
+      await addPoolLiquidity(poolYZ, 1_000_000, 1_000_000);
+    });
+
+    it("Two-hop route passes the intermediate token between vaults", async () => {
+      const yVaultXY = poolXY.tokenA.mint.equals(tokenY.mint) ? poolXY.vaultA : poolXY.vaultB;
+      const yVaultYZ = poolYZ.tokenA.mint.equals(tokenY.mint) ? poolYZ.vaultA : poolYZ.vaultB;
+      const balance = async (account: PublicKey) =>
+        Number((await getAccount(provider.connection, account)).amount);
+
+      const xBefore = await balance(walletAta(tokenX));
+      const yBefore = await balance(walletAta(tokenY));
+      const zBefore = await balance(walletAta(tokenZ));
+      const yVaultXYBefore = await balance(yVaultXY);
+      const yVaultYZBefore = await balance(yVaultYZ);
+
+      await routeSwap(tokenX, tokenZ, [
+        ...hop(poolXY, tokenX.mint),
+        ...hop(poolYZ, tokenY.mint),
+      ]);
+
+      expect(await balance(walletAta(tokenX))).to.equal(xBefore - 10_000);
+      expect(await balance(walletAta(tokenY))).to.equal(yBefore);
+      expect(await balance(walletAta(tokenZ))).to.be.greaterThan(zBefore);
+
+      // 第一跳的输出原样成为第二跳的输入
+      const yOut = yVaultXYBefore - (await balance(yVaultXY));
+      expect(yOut).to.be.greaterThan(0);
+      expect((await balance(yVaultYZ)) - yVaultYZBefore).to.equal(yOut);
+
+      expect((await program.account.pool.fetch(poolXY.pool)).swapCount.toNumber()).to.equal(1);
+      expect((await program.account.pool.fetch(poolYZ.pool)).swapCount.toNumber()).to.equal(1);
+    });
+
+    it("Hop whose pool does not hold the input mint is rejected", async () => {
+      await expectError(routeSwap(tokenX, tokenZ, hop(poolYZ, tokenY.mint)), /InvalidSwapPath/);
+      // 最后一跳的输出与用户输出账户不一致
+      await expectError(routeSwap(tokenX, tokenZ, hop(poolXY, tokenX.mint)), /InvalidSwapPath/);
+    });
+
+    it("Same pool twice in a route is rejected", async () => {
+      await expectError(
+        routeSwap(tokenX, tokenZ, [...hop(poolXY, tokenX.mint), ...hop(poolXY, tokenY.mint)]),
+        /InvalidSwapPath/
+      );
+    });
+
+    it("Wrong vault or oracle is rejected", async () => {
+      const swapped = hop(poolXY, tokenX.mint);
+      [swapped[1], swapped[2]] = [swapped[2], swapped[1]];
+      await expectError(routeSwap(tokenX, tokenY, swapped), /InvalidAccount/);
+
+      await expectError(
+        routeSwap(tokenX, tokenY, hop(poolXY, tokenX.mint, { oracle: oraclePda(poolYZ.pool) })),
+        /InvalidAccount/
+      );
+    });
+
+    it("Minimum output is enforced on the final hop", async () => {
+      const swapCountBefore = (await program.account.pool.fetch(poolXY.pool)).swapCount;
+
+      // 第一跳本身有输出，只有端到端的最少输出不满足
+      await expectError(
+        routeSwap(
+          tokenX,
+          tokenZ,
+          [...hop(poolXY, tokenX.mint), ...hop(poolYZ, tokenY.mint)],
+          new anchor.BN(1_000_000)
+        ),
+        /MinimumOutputNotMet/
+      );
+
+      // 整笔交易回滚，第一跳也没有生效
+      const pool = await program.account.pool.fetch(poolXY.pool);
+      expect(pool.swapCount.eq(swapCountBefore)).to.equal(true);
+    });
+  });
+
+  describe("Token-2022 transfer fee", () => {
+    const TRANSFER_FEE_BPS = 100; // 1%
+
+    it("Deposit records the amount the vault actually received", async () => {
+      const payer = (provider.wallet as anchor.Wallet).payer;
+
+      // 带 TransferFeeConfig 扩展的 Token-2022 mint
+      const feeMint = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: authority,
+            newAccountPubkey: feeMint.publicKey,
+            space: mintLen,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeTransferFeeConfigInstruction(
+            feeMint.publicKey,
+            authority,
+            authority,
+            TRANSFER_FEE_BPS,
+            BigInt(1_000_000_000),
+            TOKEN_2022_PROGRAM_ID
+          ),
+          createInitializeMintInstruction(
+            feeMint.publicKey,
+            6,
+            authority,
+            null,
+            TOKEN_2022_PROGRAM_ID
+          )
+        ),
+        [feeMint]
+      );
+      const feeToken = { mint: feeMint.publicKey, program: TOKEN_2022_PROGRAM_ID };
+      const feeAta = await createAssociatedTokenAccount(
+        provider.connection,
+        payer,
+        feeToken.mint,
+        authority,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(
+        provider.connection,
+        payer,
+        feeToken.mint,
+        feeAta,
+        authority,
+        1_000_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const plainToken = await createFundedMint(1_000_000_000);
+
+      const info = await createPool(feeToken, plainToken);
+      const feeIsA = info.tokenA.mint.equals(feeToken.mint);
+      const deposit = 200_000;
+      await addPoolLiquidity(info, deposit, deposit);
+
+      // 转账手续费由 Token-2022 扣留，池子只记录金库实际到账的部分
+      const net = deposit - (deposit * TRANSFER_FEE_BPS) / 10000;
+      const pool = await program.account.pool.fetch(info.pool);
+      const position = await program.account.position.fetch(positionPdaFor(info.pool));
+      const feeVault = await getAccount(
+        provider.connection,
+        feeIsA ? info.vaultA : info.vaultB,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(Number(feeVault.amount)).to.equal(net);
+      expect((feeIsA ? pool.tokenAReserve : pool.tokenBReserve).toNumber()).to.equal(net);
+      expect((feeIsA ? pool.tokenBReserve : pool.tokenAReserve).toNumber()).to.equal(deposit);
+      expect(
+        (feeIsA ? position.initialDepositA : position.initialDepositB).toNumber()
+      ).to.equal(net);
+    });
+  });
+
+  describe("Concentrated emergency withdraw", () => {
+    const TICK_SPACING = 10;
+    const TICK_LOWER = -100;
+    const TICK_UPPER = 100;
+    const LIQUIDITY = new anchor.BN(1_000_000_000);
+    let info: PoolInfo;
+
+    const i32Le = (value: number) => {
+      const buf = Buffer.alloc(4);
+      buf.writeInt32LE(value);
+      return buf;
+    };
+    const tickArrayPda = (startTickIndex: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("tick_array"), info.pool.toBuffer(), i32Le(startTickIndex)],
+        program.programId
+      )[0];
+    // 每个 TickArray 覆盖 32 个 tick 间距
+    const startIndexFor = (tick: number) => {
+      const span = 32 * TICK_SPACING;
+      return Math.floor(tick / span) * span;
+    };
+    const rangePositionPda = () =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("position"),
+          authority.toBuffer(),
+          info.pool.toBuffer(),
+          i32Le(TICK_LOWER),
+          i32Le(TICK_UPPER),
+        ],
+        program.programId
+      )[0];
+    const balanceOf = async (token: TokenInfo) =>
+      Number((await getAccount(provider.connection, walletAta(token))).amount);
+
+    const emergencyWithdrawRange = () =>
+      program.methods
+        .emergencyWithdrawRange()
+        .accounts({
+          user: authority,
+          pool: info.pool,
+          userTokenA: walletAta(info.tokenA),
+          userTokenB: walletAta(info.tokenB),
+          poolTokenAVault: info.vaultA,
+          poolTokenBVault: info.vaultB,
+          tickArrayLower: tickArrayPda(startIndexFor(TICK_LOWER)),
+          tickArrayUpper: tickArrayPda(startIndexFor(TICK_UPPER)),
+          position: rangePositionPda(),
+          tokenAMint: info.tokenA.mint,
+          tokenBMint: info.tokenB.mint,
+          tokenAProgram: info.tokenA.program,
+          tokenBProgram: info.tokenB.program,
+        })
+        .rpc();
+
+    before(async () => {
+      const x = await createFundedMint(1_000_000_000);
+      const y = await createFundedMint(1_000_000_000);
+      // 初始价格 1（Q64.64），当前 tick 为 0
+      info = await createPool(x, y, {
+        tickSpacing: TICK_SPACING,
+        sqrtPriceX64: new anchor.BN(1).shln(64),
+      });
+
+      for (const start of [startIndexFor(TICK_LOWER), startIndexFor(TICK_UPPER)]) {
+        await program.methods
+          .initializeTickArray(start)
+          .accounts({ payer: authority, pool: info.pool })
+          .rpc();
+      }
+
+      await program.methods
+        .addConcentratedLiquidity(
+          TICK_LOWER,
+          TICK_UPPER,
+          LIQUIDITY,
+          new anchor.BN(1_000_000_000),
+          new anchor.BN(1_000_000_000)
+        )
+        .accounts({
+          user: authority,
+          pool: info.pool,
+          userTokenA: walletAta(info.tokenA),
+          userTokenB: walletAta(info.tokenB),
+          poolTokenAVault: info.vaultA,
+          poolTokenBVault: info.vaultB,
+          tickArrayLower: tickArrayPda(startIndexFor(TICK_LOWER)),
+          tickArrayUpper: tickArrayPda(startIndexFor(TICK_UPPER)),
+          tokenAMint: info.tokenA.mint,
+          tokenBMint: info.tokenB.mint,
+          tokenAProgram: info.tokenA.program,
+          tokenBProgram: info.tokenB.program,
+        })
+        .rpc();
+    });
+
+    it("Rejects range emergency withdraw outside emergency mode", async () => {
+      await expectError(emergencyWithdrawRange(), /EmergencyModeNotActive/);
+    });
+
+    it("Burns the whole range position at the current price", async () => {
+      const before = await program.account.pool.fetch(info.pool);
+      const balanceABefore = await balanceOf(info.tokenA);
+      const balanceBBefore = await balanceOf(info.tokenB);
+
+      await program.methods
+        .setPoolFlag({ emergencyMode: {} }, true)
+        .accounts({ authority: authority, pool: info.pool })
+        .rpc();
+      await emergencyWithdrawRange();
+
+      const pool = await program.account.pool.fetch(info.pool);
+      const position = await program.account.position.fetch(rangePositionPda());
+      expect(position.liquidity.toNumber()).to.equal(0);
+      expect(pool.liquidity.toNumber()).to.equal(0);
+      // 价格在区间内，两种代币都被取回；取回量向下取整，池子最多留下舍入误差
+      const receivedA = (await balanceOf(info.tokenA)) - balanceABefore;
+      const receivedB = (await balanceOf(info.tokenB)) - balanceBBefore;
+      expect(receivedA).to.be.greaterThan(0);
+      expect(receivedB).to.be.greaterThan(0);
+      expect(receivedA).to.equal(before.tokenAReserve.toNumber() - pool.tokenAReserve.toNumber());
+      expect(receivedB).to.equal(before.tokenBReserve.toNumber() - pool.tokenBReserve.toNumber());
+      expect(pool.tokenAReserve.toNumber()).to.be.at.most(1);
+      expect(pool.tokenBReserve.toNumber()).to.be.at.most(1);
+
+      // 头寸已清空，再次赎回被拒绝
+      await expectError(emergencyWithdrawRange(), /InsufficientLiquidity/);
+    });
+  });
+
+  describe("Global config", () => {
+    const [globalConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global_config")],
+      program.programId
+    );
+    const fetchConfig = () => program.account.globalConfig.fetch(globalConfigPda);
+
+    it("Non-super-admin cannot update the config", async () => {
+      const intruder = Keypair.generate();
+      await expectError(
+        program.methods
+          .setGlobalFlag({ emergencyMode: {} }, true)
+          .accounts({ superAdmin: intruder.publicKey })
+          .signers([intruder])
+          .rpc(),
+        /Unauthorized/
+      );
+      await expectError(
+        program.methods
+          .updateProtocolFee(0)
+          .accounts({ superAdmin: intruder.publicKey })
+          .signers([intruder])
+          .rpc(),
+        /Unauthorized/
+      );
+      await expectError(
+        program.methods
+          .updateTreasury(intruder.publicKey)
+          .accounts({ superAdmin: intruder.publicKey })
+          .signers([intruder])
+          .rpc(),
+        /Unauthorized/
+      );
+    });
+
+    it("Pool ALM requires the global switch", async () => {
+      await expectError(
+        program.methods
+          .setPoolFlag({ alm: {} }, true)
+          .accounts({ authority: authority, pool: poolPda })
+          .rpc(),
+        /FeatureDisabledGlobally/
+      );
+
+      await program.methods
+        .setGlobalFlag({ alm: {} }, true)
+        .accounts({ superAdmin: authority })
+        .rpc();
+      expect((await fetchConfig()).almEnabledGlobally).to.equal(true);
+
+      await program.methods
+        .setPoolFlag({ alm: {} }, true)
+        .accounts({ authority: authority, pool: poolPda })
+        .rpc();
+      expect((await program.account.pool.fetch(poolPda)).almEnabled).to.equal(true);
+
+      await program.methods
+        .setGlobalFlag({ alm: {} }, false)
+        .accounts({ superAdmin: authority })
+        .rpc();
+    });
+
+    it("Global emergency blocks config updates until turned off", async () => {
+      await program.methods
+        .setGlobalFlag({ emergencyMode: {} }, true)
+        .accounts({ superAdmin: authority })
+        .rpc();
+
+      await expectError(
+        program.methods
+          .updateProtocolFee(500)
+          .accounts({ superAdmin: authority })
+          .rpc(),
+        /EmergencyModeActive/
+      );
+      // 池子自身未处于紧急模式，池子级管理指令同样被全局紧急模式拒绝
+      await expectError(
+        program.methods
+          .proposeAuthority({ authority: {} }, Keypair.generate().publicKey)
+          .accounts({ signer: authority, pool: poolPda })
+          .rpc(),
+        /EmergencyModeActive/
+      );
+
+      await program.methods
+        .setGlobalFlag({ emergencyMode: {} }, false)
+        .accounts({ superAdmin: authority })
+        .rpc();
+      expect((await fetchConfig()).emergencyMode).to.equal(false);
+    });
+
+    it("Update protocol fee", async () => {
+      await expectError(
+        program.methods
+          .updateProtocolFee(10001)
+          .accounts({ superAdmin: authority })
+          .rpc(),
+        /InvalidFeeTier/
+      );
+
+      await program.methods
+        .updateProtocolFee(500)
+        .accounts({ superAdmin: authority })
+        .rpc();
+      expect((await fetchConfig()).protocolFeeBps).to.equal(500);
+    });
+
+    it("Update treasury", async () => {
+      await expectError(
+        program.methods
+          .updateTreasury(PublicKey.default)
+          .accounts({ superAdmin: authority })
+          .rpc(),
+        /InvalidAccount/
+      );
+
+      const newTreasury = Keypair.generate().publicKey;
+      await program.methods
+        .updateTreasury(newTreasury)
+        .accounts({ superAdmin: authority })
+        .rpc();
+      expect((await fetchConfig()).treasury.toBase58()).to.equal(newTreasury.toBase58());
+    });
+
+    it("Update upgrade authority", async () => {
+      const newUpgradeAuthority = Keypair.generate().publicKey;
+      await program.methods
+        .updateUpgradeAuthority(newUpgradeAuthority)
+        .accounts({ superAdmin: authority })
+        .rpc();
+      expect((await fetchConfig()).upgradeAuthority.toBase58()).to.equal(
+        newUpgradeAuthority.toBase58()
+      );
+    });
+
+    it("Super admin can be handed over", async () => {
+      const newAdmin = Keypair.generate();
+      await program.methods
+        .updateSuperAdmin(newAdmin.publicKey)
+        .accounts({ superAdmin: authority })
+        .rpc();
+      expect((await fetchConfig()).superAdmin.toBase58()).to.equal(
+        newAdmin.publicKey.toBase58()
+      );
+
+      // 原管理员立即失去权限
+      await expectError(
+        program.methods
+          .updateProtocolFee(0)
+          .accounts({ superAdmin: authority })
+          .rpc(),
+        /Unauthorized/
+      );
+
+      await program.methods
+        .updateSuperAdmin(authority)
+        .accounts({ superAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+      expect((await fetchConfig()).superAdmin.toBase58()).to.equal(authority.toBase58());
+    });
+  });
 });